@@ -9,6 +9,299 @@ no-snapshots = No snapshots found.
 no-snapshots-suggestion = To create a snapshot use the menu.
 loading = Loading...
 loading-snapshots = Loading snapshots.
+back = Back
+
+## Snapshot list
+group-by = Group by
+group-none = Nothing
+date = Date
+today = Today
+this-week = This week
+older = Older
+unknown = Unknown
+time = Time
+host = Host
+paths = Paths
+tags = Tags
+size = Size
+added-data = Added
+filter-text = Search snapshots
+filter-hosts = Hosts
+filter-tags = Tags, e.g. daily,home
+filter-paths = Paths
+filter-after = From YYYY-MM-DD
+filter-before = Until YYYY-MM-DD
+filtered-snapshots = Showing {$shown} of {$total} snapshots
+invalid-date = Dates must be written as YYYY-MM-DD.
+clear-filter = Clear filter
+no-matching-snapshots = No snapshots match the filter.
+selected-snapshots = {$count ->
+    [one] 1 snapshot selected ({$size})
+   *[other] {$count} snapshots selected ({$size})
+}
+clear-selection = Clear selection
+add-tags = Add tags
+tags-placeholder = Tags, separated by commas
+delete-snapshots = {$count ->
+    [one] Delete 1 snapshot?
+   *[other] Delete {$count} snapshots?
+}
+delete-snapshots-description = {$count ->
+    [one] The snapshot with {$size} of data will be removed from the repository. This can be undone for a few seconds.
+   *[other] The {$count} snapshots with {$size} of data in total will be removed from the repository. This can be undone for a few seconds.
+}
+tag-snapshots = {$count ->
+    [one] Tag 1 snapshot
+   *[other] Tag {$count} snapshots
+}
+tag-snapshots-description = {$count ->
+    [one] The tags will be added to the snapshot ({$size}).
+   *[other] The tags will be added to all {$count} snapshots ({$size} in total).
+}
+snapshots-deleted = {$count ->
+    [one] Snapshot deleted
+   *[other] {$count} snapshots deleted
+}
+undo = Undo
+copy-to-repository = Copy to repository...
+copy-snapshots = {$count ->
+    [one] Copy 1 snapshot
+   *[other] Copy {$count} snapshots
+}
+copy-snapshots-description = Only data missing in the target repository is uploaded. Snapshots it already contains are skipped.
+target-password = Password of the target repository
+copy = Copy
+no-copy-targets = Add another repository to copy snapshots to.
+copy-progress = Copying to {$repository}: {$copied} of {$total} snapshots
+snapshots-copied = {$count ->
+    [one] 1 snapshot copied to {$repository}
+   *[other] {$count} snapshots copied to {$repository}
+}{$skipped ->
+    [0] {""}
+    [one] , 1 was already there
+   *[other] , {$skipped} were already there
+}
+snapshots-already-copied = {$repository} already contains the selected snapshots
+copy-failed = Copying failed: {$error}
+
+## Profiles
+profiles = Profiles
+profiles-title = Backup profiles of {$name}
+no-profiles = No profiles yet. Add one below to back up the same source again with one click.
+command-source = Output of `{$command}`, saved as {$filename}
+run = Run
+running = Running…
+new-profile = New profile
+profile-name = e.g. Documents or Database
+source = Source
+source-paths = Files and folders
+source-command = Output of a command
+profile-paths = Paths, separated by commas
+command = Command
+profile-command = e.g. pg_dump mydb
+filename = File name
+profile-filename = e.g. mydb.sql
+with-hooks = {$description}, with hooks
+pre-hook = Before the backup
+pre-hook-placeholder = Command, the backup is skipped if it fails
+post-hook = After the backup
+post-hook-placeholder = Command run after a successful backup
+failure-hook = On failure
+failure-hook-placeholder = Command run if the backup or a hook fails
+hook-timeout = Hook timeout
+hook-timeout-placeholder = Seconds, {$seconds} if empty
+parent = Parent snapshot
+parent-description = Unchanged files are taken from the parent without reading them again
+parent-latest = Latest of the same host and paths
+parent-snapshot = A specific snapshot
+parent-rescan = None, rescan all files
+parent-id = Snapshot ID
+parent-id-placeholder = ID or a unique prefix of it
+ignore-ctime = Ignore ctime
+ignore-ctime-description = Files whose permissions or owner changed are not read again
+ignore-inode = Ignore inode
+ignore-inode-description = For filesystems without stable inode numbers, such as some network shares
+with-parent = {$description}, based on {$parent}
+with-rescan = {$description}, full rescan
+add-profile = Add profile
+profile-finished = Backup {$name} finished
+snapshot-created = Snapshot created
+profile-failed = Backup {$name} failed: {$error}
+notification-finished = {$added} added in {$seconds} s
+notification-failed = Backup {$name} failed
+notification-failed-body = {$error} (after {$seconds} s)
+notification-synced = Mirror of {$name} synced
+notification-synced-body = {$count ->
+    [one] 1 snapshot copied in {$seconds} s
+   *[other] {$count} snapshots copied in {$seconds} s
+}
+notification-sync-failed = Syncing {$name} to its mirror failed
+show-snapshot = Show snapshot
+
+## Run history
+run-history = History
+runs-title = Run history of {$name}
+runs = Runs
+loading-runs = Reading the run history…
+no-runs = No backups, checks or prunes have been recorded yet.
+check = Check
+prune = Prune
+backup-of = Backup {$profile}
+run-duration = {$seconds} s
+run-summary = {$new} new, {$changed} changed, {$unmodified} unchanged files, {$added} added
+run-parent = parent {$parent}, {$reused} files reused
+run-full-scan = full scan without a parent
+run-succeeded = Succeeded
+run-failed = Failed: {$error}
+show-log = Show log
+hide-log = Hide log
+check-finished = The repository check found no errors
+prune-finished = Unused data was removed from the repository
+maintenance-failed = {$error}
+export-runs-failed = The run history could not be exported
+
+## Mirror
+mirror-synced = Mirrored to {$target}, last synced {$time}
+mirror-never-synced = Mirrored to {$target}, not synced yet
+mirror-pending = {$count ->
+    [0] up to date
+    [one] 1 snapshot pending
+   *[other] {$count} snapshots pending
+}
+mirror-unavailable = mirror unavailable: {$error}
+mirror-password-missing = The password of the mirror is not saved
+repository-password-missing = The password of the repository is not saved
+sync-now = Sync now
+syncing = Syncing…
+mirror-sync-done = {$count ->
+    [0] The mirror is up to date
+    [one] 1 snapshot copied to the mirror
+   *[other] {$count} snapshots copied to the mirror
+}
+mirror-sync-failed = Syncing the mirror failed: {$error}
+export-snapshots = {$count ->
+    [one] Export 1 snapshot
+   *[other] Export {$count} snapshots
+}
+export-snapshots-description = {$count ->
+    [one] The snapshot ({$size}) will be written as an archive into the chosen folder.
+   *[other] Each of the {$count} snapshots ({$size} in total) will be written as its own archive into the chosen folder.
+}
+just-now = Just now
+minutes-ago = {$count ->
+    [one] {$count} minute ago
+   *[other] {$count} minutes ago
+}
+hours-ago = {$count ->
+    [one] {$count} hour ago
+   *[other] {$count} hours ago
+}
+days-ago = {$count ->
+    [one] {$count} day ago
+   *[other] {$count} days ago
+}
+
+## Snapshot details
+unique-data = Unique
+computing = …
+details-title = Snapshot {$id}
+snapshot-not-found = The snapshot no longer exists.
+snapshot = Snapshot
+id = ID
+label = Label
+backup = Backup
+files = Files
+files-summary = {$new} new, {$changed} changed, {$unmodified} unmodified
+added-packed = Added after compression
+duration = Duration
+computing-cost = Computing how much space the snapshot uses…
+storage-cost = Storage cost
+unique-cost = Only in this snapshot, freed by deleting and pruning it
+shared-cost = Shared with other snapshots
+
+## Compare
+compare = Compare
+comparing = Comparing
+select-second-snapshot = Select another snapshot to compare with {$id}.
+diff-title = Changes from {$from} to {$to}
+diff-summary = {$added} added, {$removed} removed, {$modified} modified ({$delta})
+loading-changes = Loading changes...
+no-changes = No changes found.
+changes = Changes
+added = Added
+removed = Removed
+modified = Modified
+metadata-changed = Metadata changed
+more-changes = ...and {$count} more.
+compare-live = Compare with current files
+diff-live-title = Changes since {$id}
+diff-live-description = Restoring this snapshot would overwrite modified files and recreate deleted ones.
+created = New
+deleted = Deleted
+
+## Search
+search-placeholder = Search files in snapshots
+search-title = Files matching "{$query}"
+searching = Searching snapshots...
+no-results = No matching files found.
+results = Results
+match-details = Snapshot from {$time}, {$size}, modified {$mtime}
+more-results = ...and {$count} more.
+restore = Restore
+
+## History
+history = History
+history-title = Versions of {$path}
+loading-versions = Loading versions...
+no-versions = No versions of this file found.
+versions = Versions
+version-range = From {$first} to {$last}
+version-details = {$size}, modified {$mtime}, in {$count} snapshots
+
+## Preview
+preview = Preview
+no-preview = No file selected.
+loading-preview = Loading preview...
+preview-truncated = Only the first {$limit} of the file are shown.
+preview-too-large = The image is too large to preview ({$size}).
+preview-unsupported = No preview available for this file.
+save-file = Save as...
+
+## Mount
+mounted-at = Mounted at {$path}
+unmount = Unmount
+
+## Export
+export = Export
+export-snapshot = Export snapshot
+export-snapshot-description = Writes the snapshot, or a folder of it, to an archive file.
+export-path = Folder within the snapshot (empty for all files)
+
+## Statistics
+statistics = Statistics
+statistics-title = Statistics of {$name}
+loading-statistics = Reading the repository index…
+overview = Overview
+total-size = Data backed up
+unique-size = Data after deduplication
+compressed-size = Stored size
+compression-ratio = Compression ratio
+dedup-ratio = Deduplication ratio
+packs = Packs
+blobs = Blobs
+pending-delete-size = Marked for deletion
+growth = Growth
+
+## Disk usage
+disk-usage = Disk usage
+usage-title = Disk usage of {$id}
+loading-usage = Adding up the sizes of all files…
+usage-total = {$size} in {$files} files
+sort-by = Sort by
+name = Name
+folders = Folders
+files-in-folder = Files in this folder
+exclude = Exclude
 
 # Dialogs
 save = Save
@@ -26,6 +319,33 @@ create-snapshot = Create a new snapshot
 delete-repository = Delete repository
 delete-repository-description = Deleting a repository will remove all snapshots and files.
 delete-snapshot = Delete snapshot
+exclude-rules = Exclude rules
+exclude-rules-description = Files and folders matching these patterns are left out of new snapshots. Use * to match any name, e.g. /home/*/.cache.
+exclude-placeholder = Pattern
+add = Add
+close = Close
+repository-locks = Repository locks
+no-locks = No process holds a lock on this repository.
+locks-description = Other processes are working with this repository. Wait for them to finish.
+stale-locks-description = {$count ->
+    [one] A lock was left behind by a process that no longer runs. It can be removed safely.
+   *[other] {$count} locks were left behind by processes that no longer run. They can be removed safely.
+}
+lock-details = {$host}, process {$pid} of {$user}, since {$time}
+corrupt-lock-details = Unreadable lock file, written {$time}
+lock-stale = Stale
+lock-corrupt = Can be removed once it is 30 minutes old
+lock-exclusive = Exclusive, other processes have to wait
+lock-shared = Shared
+remove-stale-locks = Remove stale locks
+mirror = Mirror
+mirror-description = New snapshots are copied to the mirror, so a second copy of every backup exists, for example offsite. Scheduled syncs of repositories that are not open need their password saved in the keyring.
+no-mirror = No mirror
+mirror-manual = Only when syncing manually
+mirror-after-backup = After each backup
+mirror-nightly = Once a day
+mirror-password = Password of the mirror, saved in the keyring
+remember-password = Remember the password in the keyring
 snapshot-description = A snapshot is a point-in-time copy of your repository.
 password = Password
 
@@ -35,6 +355,12 @@ password = Password
 about = About
 git-description = Git commit {$hash}  on {$date}
 
+## Log
+log = Log
+loading-log = Reading the log…
+log-empty = Nothing was written to the log file yet.
+reload = Reload
+
 ## Settings
 settings = Settings
 
@@ -45,20 +371,40 @@ match-desktop = Match desktop
 dark = Dark
 light = Light
 
+### Backups
+backups = Backups
+stale-after = Warn about old backups after
+stale-after-description = Repositories without a newer successful backup are marked in the sidebar
+stale-days = { $days ->
+    [one] 1 day
+   *[other] {$days} days
+}
+
+### Logging
+logging = Logging
+log-file = Write a log file
+log-file-description = Keeps a week of logs in the state directory. Takes effect after a restart.
+
 # Menu
 
 ## File
 file = File
 new-repo = New repository
 new-snap = Create snapshot
+mount-repo = Mount repository...
+unmount-repo = Unmount repository
 new-window = New window
 quit = Quit
 
 ## Edit
 edit = Edit
+exclude-rules-menu = Exclude rules...
+repository-locks-menu = Repository locks...
+mirror-menu = Mirror...
 cut = Cut
 
 ## View
 view = View
 menu-settings = Settings...
+menu-log = Log...
 menu-about = About Stellarshot...
@@ -1,8 +1,10 @@
 // SPDX-License-Identifier: GPL-3.0-only
 
 use std::any::TypeId;
-use std::collections::{HashMap, VecDeque};
-use std::path::PathBuf;
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
 use std::{env, process};
 
 use ashpd::url::Url;
@@ -20,10 +22,19 @@ use cosmic::{
 use cosmic::{widget, Application, Apply, Element};
 use views::content::{self, Content};
 
-use crate::app::config::{AppTheme, Repository, CONFIG_VERSION};
+use crate::app::config::{AppTheme, Mirror, MirrorPolicy, Repository, CONFIG_VERSION};
 use crate::app::key_bind::key_binds;
 use crate::backup;
+use crate::backup::copy::Copier;
+use crate::backup::export::ArchiveFormat;
+use crate::backup::health::{self, Health, DEFAULT_STALE_DAYS};
+use crate::backup::history::{self, Operation, Run};
+use crate::backup::lock::{Lock, LockedError};
+use crate::backup::snapshot::{ChangeDetection, Parent};
+use crate::core::notifications::{self, Notification};
+use crate::core::{logs, secrets};
 use crate::fl;
+use bytesize::ByteSize;
 
 use self::icon_cache::IconCache;
 
@@ -34,11 +45,25 @@ pub mod menu;
 pub mod settings;
 pub mod views;
 
+/// Keys of the actions offered in notifications.
+const OPEN_SNAPSHOT: &str = "open-snapshot";
+const SHOW_LOG: &str = "show-log";
+const SHOW_APP_LOG: &str = "show-app-log";
+
+/// The window size is saved once it has not changed for this long, not on every step of a resize.
+const SAVE_WINDOW_SIZE_DELAY: Duration = Duration::from_millis(500);
+
+/// The choices for when a repository counts as stale, in days.
+const STALE_DAYS: [u32; 5] = [1, 3, 7, 14, 30];
+
 pub struct App {
     core: Core,
     nav_model: segmented_button::SingleSelectModel,
     content: Content,
     app_themes: Vec<String>,
+    archive_formats: Vec<String>,
+    mirror_policies: Vec<String>,
+    stale_options: Vec<String>,
     config_handler: Option<cosmic_config::Config>,
     config: config::StellarshotConfig,
     context_page: ContextPage,
@@ -46,11 +71,42 @@ pub struct App {
     dialog_text_input: widget::Id,
     key_binds: HashMap<KeyBind, Action>,
     modifiers: Modifiers,
+    mount: Option<Arc<backup::mount::Mount>>,
+    toasts: widget::toaster::Toasts<Message>,
+    copy_progress: Option<CopyProgress>,
+    /// The repositories being copied to their mirrors.
+    syncing: HashSet<PathBuf>,
+    /// The end of the log file shown in the log viewer, `None` while it loads.
+    log: Option<Result<Option<String>, String>>,
+    /// The last size the main window was resized to.
+    window_size: Option<(u32, u32)>,
+}
+
+/// A running copy of snapshots to another repository.
+struct CopyProgress {
+    target: String,
+    copied: usize,
+    total: usize,
+    /// Snapshots left out because the target already has them.
+    skipped: usize,
+}
+
+/// The repositories a copy of snapshots reads from and writes to, with their passwords.
+///
+/// The source is kept, so switching repositories while copying does not change it.
+#[derive(Debug, Clone)]
+pub struct CopyJob {
+    source: String,
+    source_password: String,
+    target: Repository,
+    target_password: String,
 }
 
 #[derive(Debug, Clone)]
 pub enum Message {
     Content(content::Message),
+    CloseToast(widget::ToastId),
+    UndoDelete(u64, widget::ToastId),
     DialogCancel,
     DialogComplete,
     DialogUpdate(DialogPage),
@@ -61,6 +117,8 @@ pub enum Message {
     Key(Modifiers, Key),
     Modifiers(Modifiers),
     WindowClose,
+    /// The pending deletes ran after closing the window was asked for.
+    DeletesFlushed(Result<(), String>),
     WindowNew,
     Repository(RepositoryAction),
     CreateSnapshot(Vec<Url>),
@@ -70,6 +128,61 @@ pub enum Message {
     DeleteRepositoryDialog,
     RequestFilesForSnapshot,
     OpenPasswordDialog(Repository),
+    /// The password saved in the keyring, if there is one and it opens the repository.
+    StoredPassword(Repository, Option<String>),
+    /// Opens the repository with its password.
+    Unlock(Repository, String),
+    RequestRestoreDestination(String, String, String),
+    Restore(String, String, String, String),
+    SaveFile(String, String, String, String),
+    RequestMountpoint,
+    Mount(String),
+    Mounted(Result<Arc<backup::mount::Mount>, String>),
+    Unmount,
+    RequestExportDestination(String, ArchiveFormat, String),
+    Export(String, ArchiveFormat, String),
+    RequestExportDirectory(Vec<rustic_core::Id>, ArchiveFormat),
+    ExportSnapshots(Vec<rustic_core::Id>, ArchiveFormat, String),
+    EditExcludes(Option<String>),
+    ShowLocks,
+    Locks(Result<Vec<Lock>, String>),
+    /// An operation failed because of locks held by other processes.
+    Locked(Vec<Lock>),
+    RemoveLocks(Vec<String>),
+    /// The number of stale locks removed.
+    LocksRemoved(Result<usize, String>),
+    CopySnapshots(Vec<rustic_core::Id>, Repository, String),
+    /// The snapshots still missing in the target out of the given number of selected ones.
+    CopyStart(CopyJob, usize, Result<Vec<rustic_core::Id>, String>),
+    CopyOpened(CopyJob, Vec<rustic_core::Id>, Result<Arc<Copier>, String>),
+    CopyNext(CopyJob, Arc<Copier>, VecDeque<rustic_core::Id>),
+    Copied(
+        CopyJob,
+        Arc<Copier>,
+        VecDeque<rustic_core::Id>,
+        Result<(), String>,
+    ),
+    EditMirror,
+    Sync,
+    /// The repository synced, how long it took and the number of snapshots copied to its mirror.
+    Synced(PathBuf, Duration, Result<usize, String>),
+    /// The health of each repository, computed in the background.
+    Health(Vec<(PathBuf, Health)>),
+    /// Checks whether the mirror policy asks for a sync.
+    SyncDue,
+    ProfileFinished(Run),
+    ProfileLocked(String, Vec<Lock>),
+    Maintained(Operation, Result<(), String>),
+    RequestRunsDestination(Vec<Run>),
+    ExportRuns(Vec<Run>, String),
+    /// The repository, the snapshot of the run and the action chosen in its notification.
+    NotificationAction(PathBuf, Option<String>, String),
+    ShowLog,
+    Log(Result<Option<String>, String>),
+    LogFile(bool),
+    StaleDays(usize),
+    WindowResized(u32, u32),
+    SaveWindowSize(u32, u32),
 }
 
 #[derive(Debug, Clone)]
@@ -82,6 +195,8 @@ pub enum RepositoryAction {
 #[derive(Clone, Copy, Debug, Eq, PartialEq)]
 pub enum ContextPage {
     About,
+    Log,
+    Preview,
     Settings,
 }
 
@@ -89,6 +204,8 @@ impl ContextPage {
     fn title(&self) -> String {
         match self {
             Self::About => fl!("about"),
+            Self::Log => fl!("log"),
+            Self::Preview => fl!("preview"),
             Self::Settings => fl!("settings"),
         }
     }
@@ -96,10 +213,23 @@ impl ContextPage {
 
 #[derive(Clone, Debug, Eq, PartialEq)]
 pub enum DialogPage {
-    Password(Repository, String),
+    /// The repository, the password typed and whether to save it in the keyring.
+    Password(Repository, String, bool),
     CreateRepository(String, String),
     CreateSnapshot(Vec<Url>),
     DeleteRepository,
+    ExportSnapshot(rustic_core::Id, String, ArchiveFormat),
+    DeleteSnapshots(Vec<rustic_core::Id>, u64),
+    TagSnapshots(Vec<rustic_core::Id>, u64, String),
+    ExportSnapshots(Vec<rustic_core::Id>, u64, ArchiveFormat),
+    /// The exclude rules and the rule being typed.
+    Excludes(Vec<String>, String),
+    Locks(Vec<Lock>),
+    /// The snapshots, the names of the other repositories, the chosen one and its password.
+    CopySnapshots(Vec<rustic_core::Id>, Vec<String>, usize, String),
+    /// The names of the possible mirrors after "none", the chosen one, the policy and a new
+    /// password for the mirror.
+    Mirror(Vec<String>, usize, MirrorPolicy, String),
 }
 
 #[derive(Clone, Debug)]
@@ -114,7 +244,13 @@ pub enum Action {
     CreateRepository,
     CreateSnapshot,
     DeleteRepository,
+    EditExcludes,
+    EditMirror,
+    MountRepository,
+    ShowLocks,
+    ShowLog,
     Settings,
+    UnmountRepository,
     WindowClose,
     WindowNew,
 }
@@ -127,7 +263,13 @@ impl MenuAction for Action {
             Action::CreateRepository => Message::RequestFileForRepository,
             Action::CreateSnapshot => Message::RequestFilesForSnapshot,
             Action::DeleteRepository => Message::DeleteRepositoryDialog,
+            Action::EditExcludes => Message::EditExcludes(None),
+            Action::EditMirror => Message::EditMirror,
+            Action::MountRepository => Message::RequestMountpoint,
             Action::Settings => Message::ToggleContextPage(ContextPage::Settings),
+            Action::ShowLocks => Message::ShowLocks,
+            Action::ShowLog => Message::ShowLog,
+            Action::UnmountRepository => Message::Unmount,
             Action::WindowClose => Message::WindowClose,
             Action::WindowNew => Message::WindowNew,
         }
@@ -139,6 +281,147 @@ impl App {
         cosmic::app::command::set_theme(self.config.app_theme.theme())
     }
 
+    /// Deletes the snapshots still in their grace period right away.
+    fn flush_deletes(&mut self) -> Command<Message> {
+        let Some(content::Command::DeleteSnapshots(repository, password, snapshots)) =
+            self.content.flush_deletes()
+        else {
+            return Command::none();
+        };
+        Command::perform(
+            async move { backup::snapshot::delete(&repository, &password, snapshots) },
+            |result| {
+                if let Err(err) = result {
+                    log::error!("failed to delete snapshots: {}", err);
+                }
+                cosmic::app::Message::None
+            },
+        )
+    }
+
+    /// The repositories snapshots of the open one can be copied to.
+    fn copy_targets(&self) -> Vec<Repository> {
+        let current = self.content.repository.as_ref().map(|r| &r.path);
+        self.config
+            .repositories
+            .iter()
+            .filter(|repository| Some(&repository.path) != current)
+            .cloned()
+            .collect()
+    }
+
+    /// Shows the new snapshot and copies it to the mirror if its policy asks for it.
+    fn after_backup(&mut self) -> Command<Message> {
+        let sync = self
+            .content
+            .repository
+            .as_ref()
+            .and_then(|repository| repository.mirror.as_ref())
+            .is_some_and(|mirror| mirror.policy == MirrorPolicy::AfterBackup);
+        let reload = self.update(Message::Content(content::Message::ReloadSnapshots));
+        match sync {
+            true => Command::batch([reload, self.update(Message::Sync)]),
+            false => reload,
+        }
+    }
+
+    /// Counts the snapshots of the open repository missing in its mirror.
+    fn mirror_status(&mut self) -> Command<Message> {
+        let Some((repository, target)) = self.mirror_target() else {
+            return Command::none();
+        };
+        let name = target.name.clone();
+        let repository = repository.path.display().to_string();
+        let password = self.content.password.clone();
+        let missing = fl!("mirror-password-missing");
+        Command::perform(
+            async move {
+                let target_password = secrets::password(&target.path).ok_or(missing)?;
+                let target = target.path.display().to_string();
+                backup::copy::pending(&repository, &password, &target, &target_password)
+                    .map_err(|e| e.to_string())
+            },
+            move |result| {
+                cosmic::app::Message::App(Message::Content(content::Message::SetMirrorStatus(
+                    name.clone(),
+                    result,
+                )))
+            },
+        )
+    }
+
+    /// Copies the new snapshots of `repository` to its mirror.
+    ///
+    /// The open repository is read with the password it was unlocked with, others need their
+    /// password saved in the keyring.
+    fn sync(&mut self, repository: Repository) -> Command<Message> {
+        let Some(mirror) = &repository.mirror else {
+            return Command::none();
+        };
+        if !self
+            .config
+            .repositories
+            .iter()
+            .any(|r| r.path == mirror.target)
+            || !self.syncing.insert(repository.path.clone())
+        {
+            return Command::none();
+        }
+        let open = self
+            .content
+            .repository
+            .as_ref()
+            .is_some_and(|r| r.path == repository.path);
+        if open {
+            self.content.syncing = true;
+        }
+        let password = open.then(|| self.content.password.clone());
+        let path = repository.path.clone();
+        let target = mirror.target.clone();
+        let password_missing = fl!("repository-password-missing");
+        let target_password_missing = fl!("mirror-password-missing");
+        Command::perform(
+            async move {
+                let started = Instant::now();
+                let sync = || {
+                    let password = password
+                        .or_else(|| secrets::password(&path))
+                        .ok_or(password_missing)?;
+                    let target_password =
+                        secrets::password(&target).ok_or(target_password_missing)?;
+                    backup::copy::sync(
+                        &path.display().to_string(),
+                        &password,
+                        &target.display().to_string(),
+                        &target_password,
+                    )
+                    .map_err(|e| e.to_string())
+                };
+                let result = sync();
+                (started.elapsed(), result)
+            },
+            move |(duration, result)| {
+                cosmic::app::Message::App(Message::Synced(
+                    repository.path.clone(),
+                    duration,
+                    result,
+                ))
+            },
+        )
+    }
+
+    /// The open repository and the repository it is mirrored to.
+    fn mirror_target(&self) -> Option<(Repository, Repository)> {
+        let repository = self.content.repository.as_ref()?;
+        let mirror = repository.mirror.as_ref()?;
+        let target = self
+            .config
+            .repositories
+            .iter()
+            .find(|r| r.path == mirror.target)?;
+        Some((repository.clone(), target.clone()))
+    }
+
     fn about(&self) -> Element<Message> {
         let cosmic_theme::Spacing { space_xxs, .. } = cosmic::theme::active().cosmic().spacing;
         let repository = "https://github.com/cosmic-utils/stellarshot";
@@ -180,18 +463,63 @@ impl App {
             AppTheme::Light => 2,
             AppTheme::System => 0,
         };
-        widget::settings::view_column(vec![widget::settings::view_section(fl!("appearance"))
-            .add(
-                widget::settings::item::builder(fl!("theme")).control(widget::dropdown(
-                    &self.app_themes,
-                    Some(app_theme_selected),
-                    Message::AppTheme,
-                )),
-            )
-            .into()])
+        let stale_days = match self.config.stale_days {
+            0 => DEFAULT_STALE_DAYS,
+            days => days,
+        };
+        widget::settings::view_column(vec![
+            widget::settings::view_section(fl!("appearance"))
+                .add(
+                    widget::settings::item::builder(fl!("theme")).control(widget::dropdown(
+                        &self.app_themes,
+                        Some(app_theme_selected),
+                        Message::AppTheme,
+                    )),
+                )
+                .into(),
+            widget::settings::view_section(fl!("backups"))
+                .add(
+                    widget::settings::item::builder(fl!("stale-after"))
+                        .description(fl!("stale-after-description"))
+                        .control(widget::dropdown(
+                            &self.stale_options,
+                            STALE_DAYS.iter().position(|days| *days == stale_days),
+                            Message::StaleDays,
+                        )),
+                )
+                .into(),
+            widget::settings::view_section(fl!("logging"))
+                .add(
+                    widget::settings::item::builder(fl!("log-file"))
+                        .description(fl!("log-file-description"))
+                        .toggler(self.config.log_file, Message::LogFile),
+                )
+                .into(),
+        ])
         .into()
     }
 
+    fn log_view(&self) -> Element<Message> {
+        let cosmic_theme::Spacing { space_s, .. } = cosmic::theme::active().cosmic().spacing;
+
+        let log: Element<Message> = match &self.log {
+            None => widget::text(fl!("loading-log")).into(),
+            Some(Ok(Some(log))) => widget::text::monotext(log.as_str()).into(),
+            Some(Ok(None)) => widget::text(fl!("log-empty")).into(),
+            Some(Err(err)) => widget::text(err.as_str()).into(),
+        };
+
+        widget::column()
+            .push(
+                widget::row()
+                    .push(widget::horizontal_space(Length::Fill))
+                    .push(widget::button::standard(fl!("reload")).on_press(Message::ShowLog)),
+            )
+            .push(log)
+            .spacing(space_s)
+            .into()
+    }
+
     fn create_nav_item(
         &mut self,
         repository: Repository,
@@ -203,6 +531,52 @@ impl App {
             .text(repository.name.clone())
             .data(repository.clone())
     }
+
+    /// Shows the health of every repository as the icon of its nav item.
+    fn refresh_health(&self) -> Command<Message> {
+        let paths: Vec<PathBuf> = self
+            .nav_model
+            .iter()
+            .filter_map(|entity| self.nav_model.data::<Repository>(entity))
+            .map(|repository| repository.path.clone())
+            .collect();
+        let stale_days = self.config.stale_days;
+        // reads the history of every repository, which may be on a slow drive
+        Command::perform(
+            async move {
+                paths
+                    .into_iter()
+                    .map(|path| {
+                        let health = health::health(&path, stale_days);
+                        (path, health)
+                    })
+                    .collect()
+            },
+            |health| cosmic::app::Message::App(Message::Health(health)),
+        )
+    }
+
+    /// Shows the health of each repository as its icon in the navigation.
+    fn set_health(&mut self, health: Vec<(PathBuf, Health)>) {
+        let entities: Vec<_> = self.nav_model.iter().collect();
+        for entity in entities {
+            let Some(repository) = self.nav_model.data::<Repository>(entity) else {
+                continue;
+            };
+            let Some((_, health)) = health.iter().find(|(path, _)| *path == repository.path) else {
+                continue;
+            };
+            let icon = match health {
+                Health::Unknown => "harddisk-symbolic",
+                Health::Ok => "emblem-ok-symbolic",
+                Health::Stale => "alarm-symbolic",
+                Health::Failed => "dialog-error-symbolic",
+                Health::CheckErrors => "dialog-warning-symbolic",
+                Health::Unavailable => "action-unavailable-symbolic",
+            };
+            self.nav_model.icon_set(entity, IconCache::get(icon, 18));
+        }
+    }
 }
 
 impl Application for App {
@@ -235,8 +609,21 @@ impl Application for App {
         let mut app = App {
             core,
             nav_model,
-            content: Content::new(),
+            content: Content::new(flags.config.list_state),
             app_themes: vec![fl!("match-desktop"), fl!("dark"), fl!("light")],
+            archive_formats: ArchiveFormat::ALL
+                .iter()
+                .map(|format| format!(".{}", format.extension()))
+                .collect(),
+            mirror_policies: vec![
+                fl!("mirror-manual"),
+                fl!("mirror-after-backup"),
+                fl!("mirror-nightly"),
+            ],
+            stale_options: STALE_DAYS
+                .iter()
+                .map(|days| fl!("stale-days", days = days))
+                .collect(),
             context_page: ContextPage::Settings,
             config_handler: flags.config_handler,
             config: flags.config,
@@ -244,14 +631,33 @@ impl Application for App {
             dialog_text_input: widget::Id::unique(),
             key_binds: key_binds(),
             modifiers: Modifiers::empty(),
+            mount: None,
+            toasts: widget::toaster::Toasts::new(Message::CloseToast),
+            copy_progress: None,
+            log: None,
+            window_size: None,
+            syncing: HashSet::new(),
         };
 
         let repositories = app.config.repositories.clone();
         for repository in repositories {
             app.create_nav_item(repository, "harddisk-symbolic");
         }
+        let health = app.refresh_health();
 
-        (app, Command::none())
+        let last = app.nav_model.iter().find(|entity| {
+            app.nav_model
+                .data::<Repository>(*entity)
+                .is_some_and(|repository| {
+                    Some(&repository.path) == app.config.last_repository.as_ref()
+                })
+        });
+        let command = match last {
+            Some(entity) => app.on_nav_select(entity),
+            None => Command::none(),
+        };
+
+        (app, Command::batch([health, command]))
     }
 
     fn context_drawer(&self) -> Option<Element<Message>> {
@@ -261,6 +667,8 @@ impl Application for App {
 
         Some(match self.context_page {
             ContextPage::About => self.about(),
+            ContextPage::Log => self.log_view(),
+            ContextPage::Preview => self.content.preview_view().map(Message::Content),
             ContextPage::Settings => self.settings(),
         })
     }
@@ -319,7 +727,7 @@ impl Application for App {
                 .secondary_action(
                     widget::button::standard(fl!("cancel")).on_press(Message::DialogCancel),
                 ),
-            DialogPage::Password(repository, password) => {
+            DialogPage::Password(repository, password, remember) => {
                 widget::dialog(format!("{} for {}", fl!("password"), repository.name))
                     .primary_action(
                         widget::button::suggested(fl!("ok"))
@@ -329,17 +737,31 @@ impl Application for App {
                         widget::button::standard(fl!("cancel")).on_press(Message::DialogCancel),
                     )
                     .control(
-                        widget::text_input("", password)
-                            .password()
-                            .label("Password")
-                            .id(self.dialog_text_input.clone())
-                            .on_input(move |password| {
-                                Message::DialogUpdate(DialogPage::Password(
-                                    repository.clone(),
-                                    password,
-                                ))
-                            })
-                            .on_submit(Message::DialogComplete),
+                        widget::column::with_children(vec![
+                            widget::text_input("", password)
+                                .password()
+                                .label("Password")
+                                .id(self.dialog_text_input.clone())
+                                .on_input(move |password| {
+                                    Message::DialogUpdate(DialogPage::Password(
+                                        repository.clone(),
+                                        password,
+                                        *remember,
+                                    ))
+                                })
+                                .on_submit(Message::DialogComplete)
+                                .into(),
+                            widget::checkbox(fl!("remember-password"), *remember)
+                                .on_toggle(move |remember| {
+                                    Message::DialogUpdate(DialogPage::Password(
+                                        repository.clone(),
+                                        password.clone(),
+                                        remember,
+                                    ))
+                                })
+                                .into(),
+                        ])
+                        .spacing(spacing.space_xxs),
                     )
             }
             DialogPage::DeleteRepository => widget::dialog(fl!("delete-repository"))
@@ -351,17 +773,324 @@ impl Application for App {
                 .secondary_action(
                     widget::button::standard(fl!("cancel")).on_press(Message::DialogCancel),
                 ),
+            DialogPage::ExportSnapshot(snapshot, path, format) => {
+                widget::dialog(fl!("export-snapshot"))
+                    .body(fl!("export-snapshot-description"))
+                    .control(
+                        widget::column::with_children(vec![
+                            widget::text_input(fl!("export-path"), path)
+                                .id(self.dialog_text_input.clone())
+                                .on_input(move |path| {
+                                    Message::DialogUpdate(DialogPage::ExportSnapshot(
+                                        *snapshot, path, *format,
+                                    ))
+                                })
+                                .on_submit(Message::DialogComplete)
+                                .into(),
+                            widget::dropdown(
+                                &self.archive_formats,
+                                ArchiveFormat::ALL.iter().position(|f| f == format),
+                                move |index| {
+                                    Message::DialogUpdate(DialogPage::ExportSnapshot(
+                                        *snapshot,
+                                        path.clone(),
+                                        ArchiveFormat::ALL[index],
+                                    ))
+                                },
+                            )
+                            .into(),
+                        ])
+                        .spacing(spacing.space_xxs),
+                    )
+                    .primary_action(
+                        widget::button::suggested(fl!("export"))
+                            .on_press_maybe(Some(Message::DialogComplete)),
+                    )
+                    .secondary_action(
+                        widget::button::standard(fl!("cancel")).on_press(Message::DialogCancel),
+                    )
+            }
+            DialogPage::Excludes(excludes, input) => {
+                let mut rules =
+                    widget::column::with_capacity(excludes.len() + 1).spacing(spacing.space_xxs);
+                for (index, exclude) in excludes.iter().enumerate() {
+                    let remaining: Vec<String> = excludes
+                        .iter()
+                        .enumerate()
+                        .filter(|(i, _)| *i != index)
+                        .map(|(_, exclude)| exclude.clone())
+                        .collect();
+                    rules = rules.push(
+                        widget::row::with_capacity(2)
+                            .align_items(Alignment::Center)
+                            .spacing(spacing.space_xxs)
+                            .push(widget::text::monotext(exclude).width(Length::Fill))
+                            .push(
+                                widget::button::icon(widget::icon::from_name(
+                                    "edit-delete-symbolic",
+                                ))
+                                .on_press(Message::DialogUpdate(
+                                    DialogPage::Excludes(remaining, input.clone()),
+                                )),
+                            ),
+                    );
+                }
+
+                let mut added = excludes.clone();
+                added.push(input.trim().to_string());
+                let add = (!input.trim().is_empty())
+                    .then(|| Message::DialogUpdate(DialogPage::Excludes(added, String::new())));
+
+                rules = rules.push(
+                    widget::row::with_capacity(2)
+                        .align_items(Alignment::Center)
+                        .spacing(spacing.space_xxs)
+                        .push(
+                            widget::text_input(fl!("exclude-placeholder"), input)
+                                .id(self.dialog_text_input.clone())
+                                .on_input(move |input| {
+                                    Message::DialogUpdate(DialogPage::Excludes(
+                                        excludes.clone(),
+                                        input,
+                                    ))
+                                })
+                                .on_submit(Message::DialogComplete),
+                        )
+                        .push(widget::button::standard(fl!("add")).on_press_maybe(add)),
+                );
+
+                widget::dialog(fl!("exclude-rules"))
+                    .body(fl!("exclude-rules-description"))
+                    .control(rules)
+                    .primary_action(
+                        widget::button::suggested(fl!("save"))
+                            .on_press_maybe(Some(Message::DialogComplete)),
+                    )
+                    .secondary_action(
+                        widget::button::standard(fl!("cancel")).on_press(Message::DialogCancel),
+                    )
+            }
+            DialogPage::CopySnapshots(snapshots, names, target, password) => {
+                let control = widget::column::with_capacity(2)
+                    .spacing(spacing.space_xxs)
+                    .push(widget::dropdown(names, Some(*target), move |index| {
+                        Message::DialogUpdate(DialogPage::CopySnapshots(
+                            snapshots.clone(),
+                            names.clone(),
+                            index,
+                            password.clone(),
+                        ))
+                    }))
+                    .push(
+                        widget::text_input(fl!("target-password"), password)
+                            .password()
+                            .id(self.dialog_text_input.clone())
+                            .on_input(move |password| {
+                                Message::DialogUpdate(DialogPage::CopySnapshots(
+                                    snapshots.clone(),
+                                    names.clone(),
+                                    *target,
+                                    password,
+                                ))
+                            })
+                            .on_submit(Message::DialogComplete),
+                    );
+
+                widget::dialog(fl!("copy-snapshots", count = snapshots.len()))
+                    .body(fl!("copy-snapshots-description"))
+                    .control(control)
+                    .primary_action(
+                        widget::button::suggested(fl!("copy")).on_press_maybe(
+                            (*target < names.len() && !password.is_empty())
+                                .then_some(Message::DialogComplete),
+                        ),
+                    )
+                    .secondary_action(
+                        widget::button::standard(fl!("cancel")).on_press(Message::DialogCancel),
+                    )
+            }
+            DialogPage::Mirror(names, target, policy, password) => {
+                let control = widget::column::with_capacity(3)
+                    .spacing(spacing.space_xxs)
+                    .push(widget::dropdown(names, Some(*target), move |index| {
+                        Message::DialogUpdate(DialogPage::Mirror(
+                            names.clone(),
+                            index,
+                            *policy,
+                            password.clone(),
+                        ))
+                    }))
+                    .push_maybe((*target > 0).then(|| {
+                        widget::dropdown(
+                            &self.mirror_policies,
+                            MirrorPolicy::ALL.iter().position(|p| p == policy),
+                            move |index| {
+                                Message::DialogUpdate(DialogPage::Mirror(
+                                    names.clone(),
+                                    *target,
+                                    MirrorPolicy::ALL[index],
+                                    password.clone(),
+                                ))
+                            },
+                        )
+                    }))
+                    .push_maybe((*target > 0).then(|| {
+                        widget::text_input(fl!("mirror-password"), password)
+                            .password()
+                            .on_input(move |password| {
+                                Message::DialogUpdate(DialogPage::Mirror(
+                                    names.clone(),
+                                    *target,
+                                    *policy,
+                                    password,
+                                ))
+                            })
+                            .on_submit(Message::DialogComplete)
+                    }));
+
+                widget::dialog(fl!("mirror"))
+                    .body(fl!("mirror-description"))
+                    .control(control)
+                    .primary_action(
+                        widget::button::suggested(fl!("save")).on_press(Message::DialogComplete),
+                    )
+                    .secondary_action(
+                        widget::button::standard(fl!("cancel")).on_press(Message::DialogCancel),
+                    )
+            }
+            DialogPage::Locks(locks) => {
+                let mut list =
+                    widget::column::with_capacity(locks.len()).spacing(spacing.space_xxs);
+                for lock in locks {
+                    let time = lock.time.format("%Y-%m-%d %H:%M").to_string();
+                    let details = match lock.corrupt {
+                        true => fl!("corrupt-lock-details", time = time),
+                        false => fl!(
+                            "lock-details",
+                            host = lock.hostname.as_str(),
+                            user = lock.username.as_str(),
+                            pid = lock.pid,
+                            time = time
+                        ),
+                    };
+                    let state = match (lock.stale, lock.corrupt, lock.exclusive) {
+                        (true, _, _) => fl!("lock-stale"),
+                        (false, true, _) => fl!("lock-corrupt"),
+                        (false, false, true) => fl!("lock-exclusive"),
+                        (false, false, false) => fl!("lock-shared"),
+                    };
+                    list = list.push(
+                        widget::column::with_capacity(2)
+                            .push(widget::text::body(details))
+                            .push(widget::text::caption(state)),
+                    );
+                }
+
+                let stale = locks.iter().filter(|lock| lock.stale).count();
+                let body = match (locks.len(), stale) {
+                    (0, _) => fl!("no-locks"),
+                    (_, 0) => fl!("locks-description"),
+                    (_, stale) => fl!("stale-locks-description", count = stale),
+                };
+                widget::dialog(fl!("repository-locks"))
+                    .body(body)
+                    .control(list)
+                    .primary_action(
+                        widget::button::destructive(fl!("remove-stale-locks"))
+                            .on_press_maybe((stale > 0).then_some(Message::DialogComplete)),
+                    )
+                    .secondary_action(
+                        widget::button::standard(fl!("close")).on_press(Message::DialogCancel),
+                    )
+            }
+            DialogPage::DeleteSnapshots(snapshots, size) => {
+                widget::dialog(fl!("delete-snapshots", count = snapshots.len()))
+                    .body(fl!(
+                        "delete-snapshots-description",
+                        count = snapshots.len(),
+                        size = ByteSize::b(*size).to_string()
+                    ))
+                    .primary_action(
+                        widget::button::destructive(fl!("delete"))
+                            .on_press_maybe(Some(Message::DialogComplete)),
+                    )
+                    .secondary_action(
+                        widget::button::standard(fl!("cancel")).on_press(Message::DialogCancel),
+                    )
+            }
+            DialogPage::TagSnapshots(snapshots, size, tags) => {
+                widget::dialog(fl!("tag-snapshots", count = snapshots.len()))
+                    .body(fl!(
+                        "tag-snapshots-description",
+                        count = snapshots.len(),
+                        size = ByteSize::b(*size).to_string()
+                    ))
+                    .control(
+                        widget::text_input(fl!("tags-placeholder"), tags)
+                            .id(self.dialog_text_input.clone())
+                            .on_input(move |tags| {
+                                Message::DialogUpdate(DialogPage::TagSnapshots(
+                                    snapshots.clone(),
+                                    *size,
+                                    tags,
+                                ))
+                            })
+                            .on_submit(Message::DialogComplete),
+                    )
+                    .primary_action(widget::button::suggested(fl!("add-tags")).on_press_maybe(
+                        (!tags.trim().is_empty()).then_some(Message::DialogComplete),
+                    ))
+                    .secondary_action(
+                        widget::button::standard(fl!("cancel")).on_press(Message::DialogCancel),
+                    )
+            }
+            DialogPage::ExportSnapshots(snapshots, size, format) => {
+                widget::dialog(fl!("export-snapshots", count = snapshots.len()))
+                    .body(fl!(
+                        "export-snapshots-description",
+                        count = snapshots.len(),
+                        size = ByteSize::b(*size).to_string()
+                    ))
+                    .control(widget::dropdown(
+                        &self.archive_formats,
+                        ArchiveFormat::ALL.iter().position(|f| f == format),
+                        move |index| {
+                            Message::DialogUpdate(DialogPage::ExportSnapshots(
+                                snapshots.clone(),
+                                *size,
+                                ArchiveFormat::ALL[index],
+                            ))
+                        },
+                    ))
+                    .primary_action(
+                        widget::button::suggested(fl!("export"))
+                            .on_press_maybe(Some(Message::DialogComplete)),
+                    )
+                    .secondary_action(
+                        widget::button::standard(fl!("cancel")).on_press(Message::DialogCancel),
+                    )
+            }
         };
 
         Some(dialog.into())
     }
 
+    /// Closing the window with the header bar unmounts the open repository and deletes the
+    /// snapshots still pending, like quitting does.
+    fn on_app_exit(&mut self) -> Option<Self::Message> {
+        Some(Message::WindowClose)
+    }
+
+    fn on_close_requested(&self, id: window::Id) -> Option<Self::Message> {
+        (id == window::Id::MAIN).then_some(Message::WindowClose)
+    }
+
     fn on_nav_select(&mut self, entity: widget::nav_bar::Id) -> Command<Self::Message> {
         let mut commands = vec![];
         self.nav_model.activate(entity);
 
         if let Some(repository) = self.nav_model.data::<Repository>(entity) {
-            println!("Selected: {:?}", repository);
+            tracing::debug!(repo = %repository.path.display(), "selected repository");
             let name = repository.name.clone();
             commands.push(self.update(Message::OpenPasswordDialog(repository.clone())));
             let window_title = format!("{} - {}", name, fl!("stellarshot"));
@@ -372,7 +1101,31 @@ impl Application for App {
     }
 
     fn view(&self) -> Element<Self::Message> {
-        widget::container(self.content.view().map(Message::Content))
+        let spacing = cosmic::theme::active().cosmic().spacing;
+
+        let progress = self.copy_progress.as_ref().map(|progress| {
+            widget::column::with_capacity(2)
+                .spacing(spacing.space_xxxs)
+                .padding([spacing.space_xxs, spacing.space_s])
+                .push(widget::text::caption(fl!(
+                    "copy-progress",
+                    repository = progress.target.as_str(),
+                    copied = progress.copied,
+                    total = progress.total
+                )))
+                .push(
+                    cosmic::iced::widget::progress_bar(
+                        0.0..=progress.total as f32,
+                        progress.copied as f32,
+                    )
+                    .height(Length::Fixed(4.0)),
+                )
+        });
+        let content = widget::column::with_capacity(2)
+            .push_maybe(progress)
+            .push(self.content.view().map(Message::Content));
+
+        widget::toaster(&self.toasts, content)
             .apply(widget::container)
             .width(Length::Fill)
             .height(Length::Fill)
@@ -394,8 +1147,14 @@ impl Application for App {
                 Event::Keyboard(KeyEvent::ModifiersChanged(modifiers)) => {
                     Some(Message::Modifiers(modifiers))
                 }
+                Event::Window(id, window::Event::Resized { width, height })
+                    if id == window::Id::MAIN =>
+                {
+                    Some(Message::WindowResized(width, height))
+                }
                 _ => None,
             }),
+            cosmic::iced::time::every(Duration::from_secs(60 * 60)).map(|_| Message::SyncDue),
             cosmic_config::config_subscription(
                 TypeId::of::<ConfigSubscription>(),
                 Self::APP_ID.into(),
@@ -464,14 +1223,17 @@ impl Application for App {
                 for command in commands {
                     match command {
                         content::Command::FetchSnapshots(repository, password) => {
-                            return Command::perform(
-                                async move { Content::snapshots(&repository, &password) },
-                                |result| {
-                                    cosmic::app::Message::App(Message::Content(
-                                        content::Message::SetSnapshots(result),
-                                    ))
-                                },
-                            )
+                            return Command::batch([
+                                Command::perform(
+                                    async move { Content::snapshots(&repository, &password) },
+                                    |result| {
+                                        cosmic::app::Message::App(Message::Content(
+                                            content::Message::SetSnapshots(result),
+                                        ))
+                                    },
+                                ),
+                                self.mirror_status(),
+                            ]);
                         }
                         content::Command::DeleteSnapshots(repository, password, snapshots) => {
                             return Command::perform(
@@ -482,69 +1244,462 @@ impl Application for App {
                                     Ok(_) => cosmic::app::Message::App(Message::Content(
                                         content::Message::ReloadSnapshots,
                                     )),
-                                    Err(_) => cosmic::app::Message::None,
+                                    Err(err) => match err.downcast_ref::<LockedError>() {
+                                        Some(locked) => cosmic::app::Message::App(Message::Locked(
+                                            locked.0.clone(),
+                                        )),
+                                        None => {
+                                            log::error!("failed to delete snapshots: {}", err);
+                                            cosmic::app::Message::None
+                                        }
+                                    },
                                 },
                             )
                         }
-                    }
-                }
-            }
-            Message::ToggleContextPage(context_page) => {
-                //TODO: ensure context menus are closed
-                if self.context_page == context_page {
-                    self.core.window.show_context = !self.core.window.show_context;
-                } else {
-                    self.context_page = context_page;
-                    self.core.window.show_context = true;
-                }
-                self.set_context_title(context_page.title());
-            }
-            Message::RequestFileForRepository => {
-                return Command::perform(
-                    async {
-                        ashpd::desktop::file_chooser::SelectedFiles::open_file()
-                            .title("Select a directory for the repository")
-                            .directory(true)
-                            .multiple(false)
-                            .send()
-                            .await
-                    },
-                    |result| match result {
-                        Ok(result) => {
-                            let Ok(files) = result.response() else {
-                                log::error!("response error");
-                                return cosmic::app::Message::None;
-                            };
-
-                            let Some(file) = files.uris().get(0) else {
-                                log::error!("no file selected");
-                                return cosmic::app::Message::None;
-                            };
-
-                            cosmic::app::Message::App(Message::OpenCreateRepositoryDialog(
-                                file.path().to_string(),
-                            ))
+                        content::Command::DiffSnapshots(repository, password, from, to) => {
+                            return Command::perform(
+                                async move {
+                                    backup::diff::diff(&repository, &password, from, to)
+                                        .map_err(|e| e.to_string())
+                                },
+                                |result| {
+                                    cosmic::app::Message::App(Message::Content(
+                                        content::Message::SetDiff(result),
+                                    ))
+                                },
+                            )
                         }
-                        Err(err) => {
-                            log::error!("failed to open file chooser: {}", err);
-                            cosmic::app::Message::None
+                        content::Command::DiffLive(repository, password, snapshot) => {
+                            return Command::perform(
+                                async move {
+                                    backup::diff::diff_live(&repository, &password, snapshot)
+                                        .map_err(|e| e.to_string())
+                                },
+                                |result| {
+                                    cosmic::app::Message::App(Message::Content(
+                                        content::Message::SetDiff(result),
+                                    ))
+                                },
+                            )
                         }
-                    },
-                )
-            }
-            Message::RequestFilesForSnapshot => {
-                return Command::perform(
-                    async {
-                        ashpd::desktop::file_chooser::SelectedFiles::open_file()
-                            .title("Select files to store in the repository")
-                            .directory(false)
-                            .multiple(true)
-                            .send()
-                            .await
-                    },
-                    |result| match result {
-                        Ok(result) => {
-                            let Ok(files) = result.response() else {
+                        content::Command::Find(repository, password, query) => {
+                            return Command::perform(
+                                async move {
+                                    backup::find::find(&repository, &password, &query)
+                                        .map_err(|e| e.to_string())
+                                },
+                                |result| {
+                                    cosmic::app::Message::App(Message::Content(
+                                        content::Message::SetMatches(result),
+                                    ))
+                                },
+                            )
+                        }
+                        content::Command::Versions(repository, password, path) => {
+                            return Command::perform(
+                                async move {
+                                    backup::find::versions(&repository, &password, &path)
+                                        .map_err(|e| e.to_string())
+                                },
+                                |result| {
+                                    cosmic::app::Message::App(Message::Content(
+                                        content::Message::SetVersions(result),
+                                    ))
+                                },
+                            )
+                        }
+                        content::Command::Preview(repository, password, snap_path) => {
+                            self.context_page = ContextPage::Preview;
+                            self.core.window.show_context = true;
+                            self.set_context_title(ContextPage::Preview.title());
+                            return Command::perform(
+                                async move {
+                                    backup::preview::preview(&repository, &password, &snap_path)
+                                        .map_err(|e| e.to_string())
+                                },
+                                |result| {
+                                    cosmic::app::Message::App(Message::Content(
+                                        content::Message::SetPreview(result),
+                                    ))
+                                },
+                            );
+                        }
+                        content::Command::SaveFile(repository, password, snap_path, name) => {
+                            return Command::perform(
+                                async move {
+                                    ashpd::desktop::file_chooser::SelectedFiles::save_file()
+                                        .title("Save file from snapshot")
+                                        .current_name(name.as_str())
+                                        .send()
+                                        .await
+                                },
+                                move |result| match result {
+                                    Ok(result) => {
+                                        let Ok(files) = result.response() else {
+                                            log::error!("response error");
+                                            return cosmic::app::Message::None;
+                                        };
+
+                                        let Some(file) = files.uris().get(0) else {
+                                            log::error!("no file selected");
+                                            return cosmic::app::Message::None;
+                                        };
+
+                                        cosmic::app::Message::App(Message::SaveFile(
+                                            repository.clone(),
+                                            password.clone(),
+                                            snap_path.clone(),
+                                            file.path().to_string(),
+                                        ))
+                                    }
+                                    Err(err) => {
+                                        log::error!("failed to open file chooser: {}", err);
+                                        cosmic::app::Message::None
+                                    }
+                                },
+                            );
+                        }
+                        content::Command::Unmount => return self.update(Message::Unmount),
+                        content::Command::Export(snapshot) => {
+                            self.dialog_pages.push_back(DialogPage::ExportSnapshot(
+                                snapshot,
+                                String::new(),
+                                ArchiveFormat::default(),
+                            ));
+                            return widget::text_input::focus(self.dialog_text_input.clone());
+                        }
+                        content::Command::SaveListState(list_state) => {
+                            config_set!(list_state, list_state);
+                        }
+                        content::Command::UpdateRepository(repository) => {
+                            let repositories = self
+                                .config
+                                .repositories
+                                .iter()
+                                .map(|r| {
+                                    if r.path == repository.path {
+                                        repository.clone()
+                                    } else {
+                                        r.clone()
+                                    }
+                                })
+                                .collect();
+                            config_set!(repositories, repositories);
+                            // not necessarily the active one, a repository being switched away
+                            // from saves its filter as well
+                            let entity = self.nav_model.iter().find(|entity| {
+                                self.nav_model
+                                    .data::<Repository>(*entity)
+                                    .is_some_and(|r| r.path == repository.path)
+                            });
+                            if let Some(entity) = entity {
+                                self.nav_model.data_set(entity, repository);
+                            }
+                        }
+                        content::Command::SaveFilterLater(edit) => {
+                            return Command::perform(
+                                tokio::time::sleep(content::FILTER_SAVE_DELAY),
+                                move |_| {
+                                    cosmic::app::Message::App(Message::Content(
+                                        content::Message::SaveFilter(edit),
+                                    ))
+                                },
+                            );
+                        }
+                        content::Command::ConfirmDelete(snapshots, size) => {
+                            self.dialog_pages
+                                .push_back(DialogPage::DeleteSnapshots(snapshots, size));
+                        }
+                        content::Command::ConfirmTag(snapshots, size) => {
+                            self.dialog_pages.push_back(DialogPage::TagSnapshots(
+                                snapshots,
+                                size,
+                                String::new(),
+                            ));
+                            return widget::text_input::focus(self.dialog_text_input.clone());
+                        }
+                        content::Command::ConfirmCopy(snapshots) => {
+                            let names: Vec<String> = self
+                                .copy_targets()
+                                .into_iter()
+                                .map(|repository| repository.name)
+                                .collect();
+                            if names.is_empty() {
+                                let toast = widget::toaster::Toast::new(fl!("no-copy-targets"));
+                                return self.toasts.push(toast).map(cosmic::app::Message::App);
+                            }
+                            self.dialog_pages.push_back(DialogPage::CopySnapshots(
+                                snapshots,
+                                names,
+                                0,
+                                String::new(),
+                            ));
+                            return widget::text_input::focus(self.dialog_text_input.clone());
+                        }
+                        content::Command::ConfirmExport(snapshots, size) => {
+                            self.dialog_pages.push_back(DialogPage::ExportSnapshots(
+                                snapshots,
+                                size,
+                                ArchiveFormat::default(),
+                            ));
+                        }
+                        content::Command::ScheduleDelete(token, count) => {
+                            let toast = widget::toaster::Toast::new(fl!(
+                                "snapshots-deleted",
+                                count = count
+                            ))
+                            .action(fl!("undo"), move |id| Message::UndoDelete(token, id));
+                            return Command::batch([
+                                self.toasts.push(toast).map(cosmic::app::Message::App),
+                                Command::perform(
+                                    tokio::time::sleep(content::DELETE_GRACE_PERIOD),
+                                    move |_| {
+                                        cosmic::app::Message::App(Message::Content(
+                                            content::Message::DeleteExpired(token),
+                                        ))
+                                    },
+                                ),
+                            ]);
+                        }
+                        content::Command::Usage(repository, password, snapshot) => {
+                            return Command::perform(
+                                async move {
+                                    backup::usage::usage(&repository, &password, snapshot)
+                                        .map_err(|e| e.to_string())
+                                },
+                                |result| {
+                                    cosmic::app::Message::App(Message::Content(
+                                        content::Message::SetUsage(result),
+                                    ))
+                                },
+                            )
+                        }
+                        content::Command::Sync => return self.update(Message::Sync),
+                        content::Command::RunProfile(profile) => {
+                            let Some(repository) = &self.content.repository else {
+                                return Command::none();
+                            };
+                            let path = repository.path.display().to_string();
+                            let password = self.content.password.clone();
+                            let excludes = repository.excludes.clone();
+                            let name = profile.name.clone();
+                            return Command::perform(
+                                async move {
+                                    let mut run = Run::start(
+                                        Path::new(&path),
+                                        Operation::Backup,
+                                        Some(profile.name.clone()),
+                                    );
+                                    let env = [
+                                        ("STELLARSHOT_PROFILE", profile.name.as_str()),
+                                        ("STELLARSHOT_REPOSITORY", path.as_str()),
+                                    ];
+                                    let (result, hooks) = backup::hooks::backup_with_hooks(
+                                        &profile.hooks,
+                                        &env,
+                                        || {
+                                            backup::snapshot::backup(
+                                                &path,
+                                                &password,
+                                                &profile.source,
+                                                &excludes,
+                                                &profile.parent,
+                                                profile.detection,
+                                            )
+                                        },
+                                    );
+                                    for hook in hooks {
+                                        log::info!("{}", hook);
+                                        run.log.push_str(&hook.to_string());
+                                    }
+                                    run.finish_backup(&result);
+                                    if let Err(err) = history::record(&run) {
+                                        log::error!("failed to record backup: {}", err);
+                                    }
+                                    (run, result)
+                                },
+                                move |(run, result)| {
+                                    let message = match result {
+                                        Err(err) => match err.downcast_ref::<LockedError>() {
+                                            Some(locked) => Message::ProfileLocked(
+                                                name.clone(),
+                                                locked.0.clone(),
+                                            ),
+                                            None => Message::ProfileFinished(run),
+                                        },
+                                        Ok(_) => Message::ProfileFinished(run),
+                                    };
+                                    cosmic::app::Message::App(message)
+                                },
+                            );
+                        }
+                        content::Command::Runs(path) => {
+                            return Command::perform(
+                                async move { history::runs(&path).map_err(|e| e.to_string()) },
+                                |result| {
+                                    cosmic::app::Message::App(Message::Content(
+                                        content::Message::SetRuns(result),
+                                    ))
+                                },
+                            )
+                        }
+                        content::Command::Maintain(operation) => {
+                            let Some(repository) = &self.content.repository else {
+                                return Command::none();
+                            };
+                            let path = repository.path.clone();
+                            let password = self.content.password.clone();
+                            return Command::perform(
+                                async move {
+                                    let mut run = Run::start(&path, operation, None);
+                                    let repository = path.display().to_string();
+                                    let result = match operation {
+                                        Operation::Check => {
+                                            backup::maintenance::check(&repository, &password)
+                                        }
+                                        Operation::Prune => {
+                                            backup::maintenance::prune(&repository, &password)
+                                        }
+                                        Operation::Backup => Ok(()),
+                                    }
+                                    .map_err(|e| e.to_string());
+                                    run.finish(result.clone().err());
+                                    if let Err(err) = history::record(&run) {
+                                        log::error!("failed to record {:?}: {}", operation, err);
+                                    }
+                                    result
+                                },
+                                move |result| {
+                                    cosmic::app::Message::App(Message::Maintained(
+                                        operation, result,
+                                    ))
+                                },
+                            );
+                        }
+                        content::Command::ExportRuns(runs) => {
+                            return self.update(Message::RequestRunsDestination(runs))
+                        }
+                        content::Command::EditExcludes(exclude) => {
+                            return self.update(Message::EditExcludes(exclude))
+                        }
+                        content::Command::Costs(repository, password) => {
+                            return Command::perform(
+                                async move {
+                                    backup::cost::costs(&repository, &password)
+                                        .map_err(|e| e.to_string())
+                                },
+                                |result| {
+                                    cosmic::app::Message::App(Message::Content(
+                                        content::Message::SetCosts(result),
+                                    ))
+                                },
+                            )
+                        }
+                        content::Command::Statistics(repository, password) => {
+                            return Command::perform(
+                                async move {
+                                    backup::stats::statistics(&repository, &password)
+                                        .map_err(|e| e.to_string())
+                                },
+                                |result| {
+                                    cosmic::app::Message::App(Message::Content(
+                                        content::Message::SetStatistics(result),
+                                    ))
+                                },
+                            )
+                        }
+                        content::Command::TagSnapshots(repository, password, snapshots, tags) => {
+                            return Command::perform(
+                                async move {
+                                    backup::snapshot::tag(&repository, &password, snapshots, tags)
+                                },
+                                |result| match result {
+                                    Ok(_) => cosmic::app::Message::App(Message::Content(
+                                        content::Message::ReloadSnapshots,
+                                    )),
+                                    Err(err) => match err.downcast_ref::<LockedError>() {
+                                        Some(locked) => cosmic::app::Message::App(Message::Locked(
+                                            locked.0.clone(),
+                                        )),
+                                        None => {
+                                            log::error!("failed to tag snapshots: {}", err);
+                                            cosmic::app::Message::None
+                                        }
+                                    },
+                                },
+                            )
+                        }
+                        content::Command::Restore(repository, password, snap_path) => {
+                            return self.update(Message::RequestRestoreDestination(
+                                repository, password, snap_path,
+                            ))
+                        }
+                    }
+                }
+            }
+            Message::CloseToast(id) => {
+                self.toasts.remove(id);
+            }
+            Message::UndoDelete(token, id) => {
+                self.toasts.remove(id);
+                return self.update(Message::Content(content::Message::UndoDelete(token)));
+            }
+            Message::ToggleContextPage(context_page) => {
+                //TODO: ensure context menus are closed
+                if self.context_page == context_page {
+                    self.core.window.show_context = !self.core.window.show_context;
+                } else {
+                    self.context_page = context_page;
+                    self.core.window.show_context = true;
+                }
+                self.set_context_title(context_page.title());
+            }
+            Message::RequestFileForRepository => {
+                return Command::perform(
+                    async {
+                        ashpd::desktop::file_chooser::SelectedFiles::open_file()
+                            .title("Select a directory for the repository")
+                            .directory(true)
+                            .multiple(false)
+                            .send()
+                            .await
+                    },
+                    |result| match result {
+                        Ok(result) => {
+                            let Ok(files) = result.response() else {
+                                log::error!("response error");
+                                return cosmic::app::Message::None;
+                            };
+
+                            let Some(file) = files.uris().get(0) else {
+                                log::error!("no file selected");
+                                return cosmic::app::Message::None;
+                            };
+
+                            cosmic::app::Message::App(Message::OpenCreateRepositoryDialog(
+                                file.path().to_string(),
+                            ))
+                        }
+                        Err(err) => {
+                            log::error!("failed to open file chooser: {}", err);
+                            cosmic::app::Message::None
+                        }
+                    },
+                )
+            }
+            Message::RequestFilesForSnapshot => {
+                return Command::perform(
+                    async {
+                        ashpd::desktop::file_chooser::SelectedFiles::open_file()
+                            .title("Select files to store in the repository")
+                            .directory(false)
+                            .multiple(true)
+                            .send()
+                            .await
+                    },
+                    |result| match result {
+                        Ok(result) => {
+                            let Ok(files) = result.response() else {
                                 log::error!("response error");
                                 return cosmic::app::Message::None;
                             };
@@ -560,6 +1715,278 @@ impl Application for App {
                     },
                 )
             }
+            Message::RequestRestoreDestination(repository, password, snap_path) => {
+                return Command::perform(
+                    async {
+                        ashpd::desktop::file_chooser::SelectedFiles::open_file()
+                            .title("Select a directory to restore into")
+                            .directory(true)
+                            .multiple(false)
+                            .send()
+                            .await
+                    },
+                    move |result| match result {
+                        Ok(result) => {
+                            let Ok(files) = result.response() else {
+                                log::error!("response error");
+                                return cosmic::app::Message::None;
+                            };
+
+                            let Some(file) = files.uris().get(0) else {
+                                log::error!("no file selected");
+                                return cosmic::app::Message::None;
+                            };
+
+                            cosmic::app::Message::App(Message::Restore(
+                                repository.clone(),
+                                password.clone(),
+                                snap_path.clone(),
+                                file.path().to_string(),
+                            ))
+                        }
+                        Err(err) => {
+                            log::error!("failed to open file chooser: {}", err);
+                            cosmic::app::Message::None
+                        }
+                    },
+                )
+            }
+            Message::Restore(repository, password, snap_path, destination) => {
+                return Command::perform(
+                    async move {
+                        backup::restore::restore(&repository, &password, &snap_path, &destination)
+                            .map_err(|e| e.to_string())
+                    },
+                    |result| {
+                        if let Err(err) = result {
+                            // TODO: Show error to user.
+                            log::error!("failed to restore: {}", err);
+                        }
+                        cosmic::app::Message::None
+                    },
+                );
+            }
+            Message::SaveFile(repository, password, snap_path, destination) => {
+                return Command::perform(
+                    async move {
+                        backup::preview::save(
+                            &repository,
+                            &password,
+                            &snap_path,
+                            Path::new(&destination),
+                        )
+                        .map_err(|e| e.to_string())
+                    },
+                    |result| {
+                        if let Err(err) = result {
+                            // TODO: Show error to user.
+                            log::error!("failed to save file: {}", err);
+                        }
+                        cosmic::app::Message::None
+                    },
+                );
+            }
+            Message::RequestMountpoint => {
+                if self.content.repository.is_none() {
+                    log::warn!("no repository selected to mount");
+                    return Command::none();
+                }
+                return Command::perform(
+                    async {
+                        ashpd::desktop::file_chooser::SelectedFiles::open_file()
+                            .title("Select an empty directory to mount the repository at")
+                            .directory(true)
+                            .multiple(false)
+                            .send()
+                            .await
+                    },
+                    |result| match result {
+                        Ok(result) => {
+                            let Ok(files) = result.response() else {
+                                log::error!("response error");
+                                return cosmic::app::Message::None;
+                            };
+
+                            let Some(file) = files.uris().get(0) else {
+                                log::error!("no file selected");
+                                return cosmic::app::Message::None;
+                            };
+
+                            cosmic::app::Message::App(Message::Mount(file.path().to_string()))
+                        }
+                        Err(err) => {
+                            log::error!("failed to open file chooser: {}", err);
+                            cosmic::app::Message::None
+                        }
+                    },
+                );
+            }
+            Message::Mount(mountpoint) => {
+                let Some(repository) = &self.content.repository else {
+                    return Command::none();
+                };
+                // only one repository is mounted at a time
+                if let Some(mount) = self.mount.take() {
+                    mount.unmount();
+                }
+                let repository = repository.path.display().to_string();
+                let password = self.content.password.clone();
+                return Command::perform(
+                    async move {
+                        backup::mount::mount(&repository, &password, Path::new(&mountpoint))
+                            .map(Arc::new)
+                            .map_err(|e| e.to_string())
+                    },
+                    |result| cosmic::app::Message::App(Message::Mounted(result)),
+                );
+            }
+            Message::Mounted(result) => match result {
+                Ok(mount) => {
+                    if let Err(err) = open::that_detached(&mount.mountpoint) {
+                        log::warn!("failed to open {:?}: {}", mount.mountpoint, err);
+                    }
+                    self.content.mount = self
+                        .content
+                        .repository
+                        .as_ref()
+                        .map(|repository| (repository.path.clone(), mount.mountpoint.clone()));
+                    self.mount = Some(mount);
+                }
+                Err(err) => {
+                    // TODO: Show error to user.
+                    log::error!("failed to mount repository: {}", err)
+                }
+            },
+            Message::Unmount => {
+                if let Some(mount) = self.mount.take() {
+                    mount.unmount();
+                }
+                self.content.mount = None;
+            }
+            Message::RequestExportDestination(snap_path, format, name) => {
+                return Command::perform(
+                    async move {
+                        ashpd::desktop::file_chooser::SelectedFiles::save_file()
+                            .title("Export snapshot")
+                            .current_name(name.as_str())
+                            .send()
+                            .await
+                    },
+                    move |result| match result {
+                        Ok(result) => {
+                            let Ok(files) = result.response() else {
+                                log::error!("response error");
+                                return cosmic::app::Message::None;
+                            };
+
+                            let Some(file) = files.uris().get(0) else {
+                                log::error!("no file selected");
+                                return cosmic::app::Message::None;
+                            };
+
+                            cosmic::app::Message::App(Message::Export(
+                                snap_path.clone(),
+                                format,
+                                file.path().to_string(),
+                            ))
+                        }
+                        Err(err) => {
+                            log::error!("failed to open file chooser: {}", err);
+                            cosmic::app::Message::None
+                        }
+                    },
+                );
+            }
+            Message::Export(snap_path, format, destination) => {
+                let Some(repository) = &self.content.repository else {
+                    return Command::none();
+                };
+                let repository = repository.path.display().to_string();
+                let password = self.content.password.clone();
+                return Command::perform(
+                    async move {
+                        backup::export::export(
+                            &repository,
+                            &password,
+                            &snap_path,
+                            format,
+                            Path::new(&destination),
+                        )
+                        .map_err(|e| e.to_string())
+                    },
+                    |result| {
+                        if let Err(err) = result {
+                            // TODO: Show error to user.
+                            log::error!("failed to export snapshot: {}", err);
+                        }
+                        cosmic::app::Message::None
+                    },
+                );
+            }
+            Message::RequestExportDirectory(snapshots, format) => {
+                return Command::perform(
+                    async {
+                        ashpd::desktop::file_chooser::SelectedFiles::open_file()
+                            .title("Select a directory to export into")
+                            .directory(true)
+                            .multiple(false)
+                            .send()
+                            .await
+                    },
+                    move |result| match result {
+                        Ok(result) => {
+                            let Ok(files) = result.response() else {
+                                log::error!("response error");
+                                return cosmic::app::Message::None;
+                            };
+
+                            let Some(file) = files.uris().get(0) else {
+                                log::error!("no file selected");
+                                return cosmic::app::Message::None;
+                            };
+
+                            cosmic::app::Message::App(Message::ExportSnapshots(
+                                snapshots.clone(),
+                                format,
+                                file.path().to_string(),
+                            ))
+                        }
+                        Err(err) => {
+                            log::error!("failed to open file chooser: {}", err);
+                            cosmic::app::Message::None
+                        }
+                    },
+                );
+            }
+            Message::ExportSnapshots(snapshots, format, directory) => {
+                let Some(repository) = &self.content.repository else {
+                    return Command::none();
+                };
+                let repository = repository.path.display().to_string();
+                let password = self.content.password.clone();
+                return Command::perform(
+                    async move {
+                        for snapshot in snapshots {
+                            let destination = Path::new(&directory).join(format!(
+                                "{}.{}",
+                                snapshot,
+                                format.extension()
+                            ));
+                            if let Err(err) = backup::export::export(
+                                &repository,
+                                &password,
+                                snapshot.to_hex().as_str(),
+                                format,
+                                &destination,
+                            ) {
+                                // TODO: Show error to user.
+                                log::error!("failed to export snapshot {}: {}", snapshot, err);
+                            }
+                        }
+                    },
+                    |_| cosmic::app::Message::None,
+                );
+            }
             Message::OpenCreateRepositoryDialog(path) => {
                 self.dialog_pages
                     .push_back(DialogPage::CreateRepository(path, String::new()));
@@ -570,15 +1997,53 @@ impl Application for App {
                     .push_back(DialogPage::CreateSnapshot(files));
             }
             Message::OpenPasswordDialog(repository) => {
-                let Some(current_repository) = &self.content.repository else {
-                    self.dialog_pages
-                        .push_back(DialogPage::Password(repository, String::new()));
-                    return Command::none();
-                };
-                if &repository != current_repository {
-                    self.dialog_pages
-                        .push_back(DialogPage::Password(repository, String::new()));
+                if let Some(current_repository) = &self.content.repository {
+                    if repository.path == current_repository.path {
+                        return Command::none();
+                    }
+                }
+                // repositories whose password is in the keyring open without asking, as long as it
+                // is still the right one
+                let path = repository.path.clone();
+                return Command::perform(
+                    async move {
+                        let password = secrets::password(&path)?;
+                        match backup::snapshot::fetch(&path.display().to_string(), &password) {
+                            Ok(_) => Some(password),
+                            Err(err) => {
+                                log::warn!("saved password of {} failed: {}", path.display(), err);
+                                None
+                            }
+                        }
+                    },
+                    move |password| {
+                        cosmic::app::Message::App(Message::StoredPassword(
+                            repository.clone(),
+                            password,
+                        ))
+                    },
+                );
+            }
+            Message::StoredPassword(repository, password) => match password {
+                Some(password) => return self.update(Message::Unlock(repository, password)),
+                None => self.dialog_pages.push_back(DialogPage::Password(
+                    repository,
+                    String::new(),
+                    false,
+                )),
+            },
+            Message::Unlock(repository, password) => {
+                if self.config.last_repository.as_ref() != Some(&repository.path) {
+                    config_set!(last_repository, Some(repository.path.clone()));
                 }
+                let flush = self.flush_deletes();
+                self.content.syncing = self.syncing.contains(&repository.path);
+                return Command::batch([
+                    flush,
+                    self.update(Message::Content(content::Message::SetRepository(
+                        repository, password,
+                    ))),
+                ]);
             }
             Message::Repository(state) => match state {
                 RepositoryAction::Init(path, password) => {
@@ -591,6 +2056,7 @@ impl Application for App {
                     let repository = Repository {
                         name,
                         path: PathBuf::from(&path),
+                        ..Default::default()
                     };
                     self.create_nav_item(repository.clone(), "timer-sand-symbolic");
                     return Command::perform(
@@ -606,17 +2072,441 @@ impl Application for App {
                     );
                 }
                 RepositoryAction::Created(repository) => {
-                    if self.nav_model.active_data::<Repository>().is_some() {
-                        let entity = self.nav_model.active();
-                        self.nav_model
-                            .icon_set(entity, IconCache::get("harddisk-symbolic", 18));
-                    }
                     let mut repositories = self.config.repositories.clone();
                     repositories.push(repository);
                     config_set!(repositories, repositories);
+                    return self.refresh_health();
                 }
                 RepositoryAction::Error(error) => log::error!("{}", error),
             },
+            Message::EditExcludes(exclude) => {
+                if let Some(repository) = &self.content.repository {
+                    self.dialog_pages.push_back(DialogPage::Excludes(
+                        repository.excludes.clone(),
+                        exclude.unwrap_or_default(),
+                    ));
+                    return widget::text_input::focus(self.dialog_text_input.clone());
+                }
+            }
+            Message::EditMirror => {
+                if let Some(repository) = &self.content.repository {
+                    let targets = self.copy_targets();
+                    let mirror = repository.mirror.as_ref();
+                    let target = mirror
+                        .and_then(|mirror| targets.iter().position(|r| r.path == mirror.target))
+                        .map_or(0, |index| index + 1);
+                    let mut names = vec![fl!("no-mirror")];
+                    names.extend(targets.into_iter().map(|repository| repository.name));
+                    self.dialog_pages.push_back(DialogPage::Mirror(
+                        names,
+                        target,
+                        mirror.map(|mirror| mirror.policy).unwrap_or_default(),
+                        String::new(),
+                    ));
+                }
+            }
+            Message::Sync => {
+                if let Some(repository) = self.content.repository.clone() {
+                    return self.sync(repository);
+                }
+            }
+            Message::Synced(path, duration, result) => {
+                self.syncing.remove(&path);
+                let open = self
+                    .content
+                    .repository
+                    .as_ref()
+                    .is_some_and(|repository| repository.path == path);
+                if open {
+                    self.content.syncing = false;
+                }
+                let Some(mut repository) = self
+                    .config
+                    .repositories
+                    .iter()
+                    .find(|repository| repository.path == path)
+                    .cloned()
+                else {
+                    return Command::none();
+                };
+                let notification = notify_sync(&repository, duration, &result);
+                match result {
+                    Ok(count) => {
+                        let Some(mirror) = repository.mirror.as_mut() else {
+                            return notification;
+                        };
+                        mirror.last_synced = Some(chrono::Local::now());
+                        if !open {
+                            log::info!(
+                                "copied {} snapshot(s) of {} to its mirror",
+                                count,
+                                repository.name
+                            );
+                            let repositories = self
+                                .config
+                                .repositories
+                                .iter()
+                                .map(|r| match r.path == path {
+                                    true => repository.clone(),
+                                    false => r.clone(),
+                                })
+                                .collect();
+                            config_set!(repositories, repositories);
+                            let entity = self.nav_model.iter().find(|entity| {
+                                self.nav_model
+                                    .data::<Repository>(*entity)
+                                    .is_some_and(|r| r.path == path)
+                            });
+                            if let Some(entity) = entity {
+                                self.nav_model.data_set(entity, repository);
+                            }
+                            return notification;
+                        }
+                        let toast =
+                            widget::toaster::Toast::new(fl!("mirror-sync-done", count = count));
+                        return Command::batch([
+                            self.update(Message::Content(content::Message::SetMirror(
+                                repository.mirror,
+                            ))),
+                            self.mirror_status(),
+                            self.toasts.push(toast).map(cosmic::app::Message::App),
+                            notification,
+                        ]);
+                    }
+                    Err(err) => {
+                        log::error!("failed to sync {} to its mirror: {}", repository.name, err);
+                        if !open {
+                            return notification;
+                        }
+                        let toast =
+                            widget::toaster::Toast::new(fl!("mirror-sync-failed", error = err));
+                        return Command::batch([
+                            self.toasts.push(toast).map(cosmic::app::Message::App),
+                            notification,
+                        ]);
+                    }
+                }
+            }
+            Message::ProfileFinished(run) => {
+                self.content.running = None;
+                let health = self.refresh_health();
+                let name = run.profile.clone().unwrap_or_default();
+                let notification = notify_backup(&run);
+                match &run.error {
+                    None => {
+                        let finished = fl!("profile-finished", name = name.as_str());
+                        let toast = widget::toaster::Toast::new(match &run.summary {
+                            Some(summary) => format!("{finished}: {}", content::reuse(summary)),
+                            None => finished,
+                        });
+                        return Command::batch([
+                            self.toasts.push(toast).map(cosmic::app::Message::App),
+                            self.after_backup(),
+                            notification,
+                            health,
+                        ]);
+                    }
+                    Some(err) => {
+                        log::error!("backup {} failed: {}", name, err);
+                        let toast = widget::toaster::Toast::new(fl!(
+                            "profile-failed",
+                            name = name.as_str(),
+                            error = err.as_str()
+                        ));
+                        return Command::batch([
+                            self.toasts.push(toast).map(cosmic::app::Message::App),
+                            notification,
+                            health,
+                        ]);
+                    }
+                }
+            }
+            Message::Health(health) => self.set_health(health),
+            Message::NotificationAction(repository, snapshot, action) => {
+                let focus = window::gain_focus(window::Id::MAIN);
+                if action == SHOW_APP_LOG {
+                    return Command::batch([focus, self.update(Message::ShowLog)]);
+                }
+                let active = self
+                    .content
+                    .repository
+                    .as_ref()
+                    .is_some_and(|active| active.path == repository);
+                if !active {
+                    // the snapshots and runs of another repository cannot be shown without its
+                    // password
+                    return focus;
+                }
+                let message = match action.as_str() {
+                    OPEN_SNAPSHOT => snapshot
+                        .and_then(|snapshot| rustic_core::Id::from_hex(&snapshot).ok())
+                        .map(content::Message::Select),
+                    SHOW_LOG => Some(content::Message::Runs),
+                    _ => None,
+                };
+                return match message {
+                    Some(message) => {
+                        Command::batch([focus, self.update(Message::Content(message))])
+                    }
+                    None => focus,
+                };
+            }
+            Message::Maintained(operation, result) => {
+                self.content.maintenance = None;
+                let health = self.refresh_health();
+                let toast = match (operation, result) {
+                    (Operation::Check, Ok(())) => fl!("check-finished"),
+                    (Operation::Prune, Ok(())) => fl!("prune-finished"),
+                    (_, Ok(())) => return health,
+                    (_, Err(err)) => {
+                        log::error!("{:?} failed: {}", operation, err);
+                        fl!("maintenance-failed", error = err)
+                    }
+                };
+                return Command::batch([
+                    self.toasts
+                        .push(widget::toaster::Toast::new(toast))
+                        .map(cosmic::app::Message::App),
+                    self.update(Message::Content(content::Message::Runs)),
+                    health,
+                ]);
+            }
+            Message::RequestRunsDestination(runs) => {
+                return Command::perform(
+                    async {
+                        ashpd::desktop::file_chooser::SelectedFiles::save_file()
+                            .title("Export run history")
+                            .current_name("stellarshot-history.json")
+                            .send()
+                            .await
+                    },
+                    move |result| match result {
+                        Ok(result) => {
+                            let Ok(files) = result.response() else {
+                                log::error!("response error");
+                                return cosmic::app::Message::None;
+                            };
+
+                            let Some(file) = files.uris().get(0) else {
+                                log::error!("no file selected");
+                                return cosmic::app::Message::None;
+                            };
+
+                            cosmic::app::Message::App(Message::ExportRuns(
+                                runs.clone(),
+                                file.path().to_string(),
+                            ))
+                        }
+                        Err(err) => {
+                            log::error!("failed to open file chooser: {}", err);
+                            cosmic::app::Message::None
+                        }
+                    },
+                );
+            }
+            Message::ExportRuns(runs, destination) => {
+                if let Err(err) = history::export(&runs, Path::new(&destination)) {
+                    log::error!("failed to export run history: {}", err);
+                    let toast = widget::toaster::Toast::new(fl!("export-runs-failed"));
+                    return self.toasts.push(toast).map(cosmic::app::Message::App);
+                }
+            }
+            Message::ProfileLocked(_, locks) => {
+                self.content.running = None;
+                return self.update(Message::Locked(locks));
+            }
+            Message::SyncDue => {
+                // backups become stale without anything happening
+                let health = self.refresh_health();
+                let now = chrono::Local::now();
+                let due: Vec<Repository> = self
+                    .config
+                    .repositories
+                    .iter()
+                    .filter(|repository| {
+                        repository
+                            .mirror
+                            .as_ref()
+                            .is_some_and(|mirror| mirror.policy.is_due(mirror.last_synced, now))
+                    })
+                    .cloned()
+                    .collect();
+                let mut commands: Vec<_> = due
+                    .into_iter()
+                    .map(|repository| self.sync(repository))
+                    .collect();
+                commands.push(health);
+                return Command::batch(commands);
+            }
+            Message::ShowLocks => {
+                let Some(repository) = &self.content.repository else {
+                    return Command::none();
+                };
+                let repository = repository.path.display().to_string();
+                let password = self.content.password.clone();
+                return Command::perform(
+                    async move {
+                        backup::lock::locks(&repository, &password).map_err(|e| e.to_string())
+                    },
+                    |result| cosmic::app::Message::App(Message::Locks(result)),
+                );
+            }
+            Message::Locks(result) => match result {
+                Ok(locks) => self.dialog_pages.push_back(DialogPage::Locks(locks)),
+                Err(err) => log::error!("failed to read locks: {}", err),
+            },
+            Message::Locked(locks) => {
+                self.dialog_pages.push_back(DialogPage::Locks(locks));
+                // deletions hidden during their grace period show up again
+                return self.update(Message::Content(content::Message::ReloadSnapshots));
+            }
+            Message::RemoveLocks(ids) => {
+                let Some(repository) = &self.content.repository else {
+                    return Command::none();
+                };
+                let repository = repository.path.display().to_string();
+                let password = self.content.password.clone();
+                return Command::perform(
+                    async move {
+                        backup::lock::remove(&repository, &password, &ids)
+                            .map_err(|e| e.to_string())
+                    },
+                    |result| cosmic::app::Message::App(Message::LocksRemoved(result)),
+                );
+            }
+            Message::LocksRemoved(result) => match result {
+                Ok(count) => log::info!("removed {} stale lock(s)", count),
+                Err(err) => log::error!("failed to remove locks: {}", err),
+            },
+            Message::CopySnapshots(snapshots, target, target_password) => {
+                let Some(repository) = &self.content.repository else {
+                    return Command::none();
+                };
+                let job = CopyJob {
+                    source: repository.path.display().to_string(),
+                    source_password: self.content.password.clone(),
+                    target,
+                    target_password,
+                };
+                let count = snapshots.len();
+                return Command::perform(
+                    {
+                        let job = job.clone();
+                        async move {
+                            backup::copy::missing(
+                                &job.source,
+                                &job.source_password,
+                                &job.target.path.display().to_string(),
+                                &job.target_password,
+                                &snapshots,
+                            )
+                            .map_err(|e| e.to_string())
+                        }
+                    },
+                    move |result| {
+                        cosmic::app::Message::App(Message::CopyStart(job.clone(), count, result))
+                    },
+                );
+            }
+            Message::CopyStart(job, count, result) => match result {
+                Ok(missing) if missing.is_empty() => {
+                    let toast = widget::toaster::Toast::new(fl!(
+                        "snapshots-already-copied",
+                        repository = job.target.name.as_str()
+                    ));
+                    return self.toasts.push(toast).map(cosmic::app::Message::App);
+                }
+                Ok(missing) => {
+                    self.copy_progress = Some(CopyProgress {
+                        target: job.target.name.clone(),
+                        copied: 0,
+                        total: missing.len(),
+                        skipped: count - missing.len(),
+                    });
+                    return Command::perform(
+                        {
+                            let job = job.clone();
+                            async move {
+                                Copier::open(
+                                    &job.source,
+                                    &job.source_password,
+                                    &job.target.path.display().to_string(),
+                                    &job.target_password,
+                                )
+                                .map(Arc::new)
+                                .map_err(|e| e.to_string())
+                            }
+                        },
+                        move |result| {
+                            cosmic::app::Message::App(Message::CopyOpened(
+                                job.clone(),
+                                missing.clone(),
+                                result,
+                            ))
+                        },
+                    );
+                }
+                Err(err) => {
+                    log::error!(
+                        "failed to compare snapshots with {}: {}",
+                        job.target.name,
+                        err
+                    );
+                    let toast = widget::toaster::Toast::new(fl!("copy-failed", error = err));
+                    return self.toasts.push(toast).map(cosmic::app::Message::App);
+                }
+            },
+            Message::CopyOpened(job, snapshots, result) => match result {
+                Ok(copier) => return self.update(Message::CopyNext(job, copier, snapshots.into())),
+                Err(err) => {
+                    log::error!("failed to open {} for copying: {}", job.target.name, err);
+                    self.copy_progress = None;
+                    let toast = widget::toaster::Toast::new(fl!("copy-failed", error = err));
+                    return self.toasts.push(toast).map(cosmic::app::Message::App);
+                }
+            },
+            Message::CopyNext(job, copier, mut snapshots) => {
+                let Some(snapshot) = snapshots.pop_front() else {
+                    let Some(progress) = self.copy_progress.take() else {
+                        return Command::none();
+                    };
+                    let toast = widget::toaster::Toast::new(fl!(
+                        "snapshots-copied",
+                        count = progress.copied,
+                        skipped = progress.skipped,
+                        repository = progress.target
+                    ));
+                    return self.toasts.push(toast).map(cosmic::app::Message::App);
+                };
+                return Command::perform(
+                    {
+                        let copier = Arc::clone(&copier);
+                        async move { copier.copy(snapshot).map_err(|e| e.to_string()) }
+                    },
+                    move |result| {
+                        cosmic::app::Message::App(Message::Copied(
+                            job.clone(),
+                            copier.clone(),
+                            snapshots.clone(),
+                            result,
+                        ))
+                    },
+                );
+            }
+            Message::Copied(job, copier, snapshots, result) => match result {
+                Ok(()) => {
+                    if let Some(progress) = &mut self.copy_progress {
+                        progress.copied += 1;
+                    }
+                    return self.update(Message::CopyNext(job, copier, snapshots));
+                }
+                Err(err) => {
+                    log::error!("failed to copy snapshot to {}: {}", job.target.name, err);
+                    self.copy_progress = None;
+                    let toast = widget::toaster::Toast::new(fl!("copy-failed", error = err));
+                    return self.toasts.push(toast).map(cosmic::app::Message::App);
+                }
+            },
             Message::DeleteRepositoryDialog => {
                 self.dialog_pages.push_back(DialogPage::DeleteRepository);
             }
@@ -625,18 +2515,51 @@ impl Application for App {
                     let Some(path) = repository.path.to_str() else {
                         return Command::none();
                     };
-                    match crate::backup::snapshot(
+                    let mut run = Run::start(&repository.path, Operation::Backup, None);
+                    let result = crate::backup::snapshot::snapshot_excluding(
                         path,
                         &self.content.password,
                         files.iter().map(|f| f.path()).collect(),
-                    ) {
+                        &repository.excludes,
+                        &Parent::default(),
+                        ChangeDetection::default(),
+                    );
+                    run.finish_backup(&result);
+                    if let Err(err) = history::record(&run) {
+                        log::error!("failed to record backup: {}", err);
+                    }
+                    let health = self.refresh_health();
+                    match result {
                         Ok(_) => {
-                            return self.update(Message::Content(content::Message::ReloadSnapshots))
-                        }
-                        Err(e) => {
-                            // TODO: Show error to user.
-                            log::error!("failed to create snapshot: {}", e)
+                            let toast = widget::toaster::Toast::new(match &run.summary {
+                                Some(summary) => {
+                                    format!(
+                                        "{}: {}",
+                                        fl!("snapshot-created"),
+                                        content::reuse(summary)
+                                    )
+                                }
+                                None => fl!("snapshot-created"),
+                            });
+                            return Command::batch([
+                                self.toasts.push(toast).map(cosmic::app::Message::App),
+                                self.after_backup(),
+                                health,
+                            ]);
                         }
+                        Err(e) => match e.downcast_ref::<LockedError>() {
+                            Some(locked) => {
+                                return Command::batch([
+                                    self.update(Message::Locked(locked.0.clone())),
+                                    health,
+                                ])
+                            }
+                            None => {
+                                // TODO: Show error to user.
+                                log::error!("failed to create snapshot: {}", e);
+                                return health;
+                            }
+                        },
                     }
                 }
             }
@@ -654,10 +2577,27 @@ impl Application for App {
                         DialogPage::CreateSnapshot(files) => {
                             return self.update(Message::CreateSnapshot(files));
                         }
-                        DialogPage::Password(repository, password) => {
-                            return self.update(Message::Content(content::Message::SetRepository(
-                                repository, password,
-                            )));
+                        DialogPage::Password(repository, password, remember) => {
+                            let unlock =
+                                self.update(Message::Unlock(repository.clone(), password.clone()));
+                            if !remember {
+                                return unlock;
+                            }
+                            // only a password that opens the repository is saved
+                            let save = Command::perform(
+                                async move {
+                                    let path = repository.path.display().to_string();
+                                    backup::snapshot::fetch(&path, &password)?;
+                                    secrets::set_password(&repository.path, &password)
+                                },
+                                |result| {
+                                    if let Err(err) = result {
+                                        log::error!("failed to save password: {}", err);
+                                    }
+                                    cosmic::app::Message::None
+                                },
+                            );
+                            return Command::batch([unlock, save]);
                         }
                         DialogPage::DeleteRepository => {
                             if let Some(repository) = self.content.repository.clone() {
@@ -668,12 +2608,120 @@ impl Application for App {
                                         .filter(|r| r.path != repository.path)
                                         .collect();
                                     config_set!(repositories, repositories);
+                                    if let Err(err) = secrets::delete_password(&repository.path) {
+                                        log::error!("failed to delete password: {}", err);
+                                    }
                                     let entity = self.nav_model.active();
                                     self.nav_model.remove(entity);
+                                    // the snapshots are gone with the repository
+                                    let _ = self.content.flush_deletes();
                                     self.content.repository = None;
                                 }
                             }
                         }
+                        DialogPage::ExportSnapshot(snapshot, path, format) => {
+                            let id = snapshot.to_hex();
+                            let snap_path = match path.trim() {
+                                "" => id.as_str().to_string(),
+                                path => format!("{}:{}", id.as_str(), path),
+                            };
+                            let name = format!("{}.{}", snapshot, format.extension());
+                            return self.update(Message::RequestExportDestination(
+                                snap_path, format, name,
+                            ));
+                        }
+                        DialogPage::Excludes(mut excludes, input) => {
+                            // a rule typed but not added yet is most likely wanted too
+                            if !input.trim().is_empty() {
+                                excludes.push(input.trim().to_string());
+                            }
+                            return self
+                                .update(Message::Content(content::Message::SetExcludes(excludes)));
+                        }
+                        DialogPage::CopySnapshots(snapshots, _, target, password) => {
+                            if let Some(target) = self.copy_targets().into_iter().nth(target) {
+                                return self
+                                    .update(Message::CopySnapshots(snapshots, target, password));
+                            }
+                        }
+                        DialogPage::Mirror(_, target, policy, password) => {
+                            let mirror = match target.checked_sub(1) {
+                                Some(index) => {
+                                    let Some(target) = self.copy_targets().into_iter().nth(index)
+                                    else {
+                                        return Command::none();
+                                    };
+                                    if !password.is_empty() {
+                                        if let Err(err) =
+                                            secrets::set_password(&target.path, &password)
+                                        {
+                                            log::error!("failed to save mirror password: {}", err);
+                                        }
+                                    }
+                                    // keep the last sync when only the policy changes
+                                    let last_synced = self
+                                        .content
+                                        .repository
+                                        .as_ref()
+                                        .and_then(|repository| repository.mirror.as_ref())
+                                        .filter(|mirror| mirror.target == target.path)
+                                        .and_then(|mirror| mirror.last_synced);
+                                    Some(Mirror {
+                                        target: target.path,
+                                        policy,
+                                        last_synced,
+                                    })
+                                }
+                                None => None,
+                            };
+                            // the password of a mirror no longer used is not needed anymore
+                            let previous = self
+                                .content
+                                .repository
+                                .as_ref()
+                                .and_then(|repository| repository.mirror.as_ref())
+                                .map(|mirror| mirror.target.clone())
+                                .filter(|previous| {
+                                    Some(previous) != mirror.as_ref().map(|mirror| &mirror.target)
+                                });
+                            let command =
+                                self.update(Message::Content(content::Message::SetMirror(mirror)));
+                            if let Some(previous) = previous {
+                                let used = self.config.repositories.iter().any(|repository| {
+                                    repository
+                                        .mirror
+                                        .as_ref()
+                                        .is_some_and(|mirror| mirror.target == previous)
+                                });
+                                if !used {
+                                    if let Err(err) = secrets::delete_password(&previous) {
+                                        log::error!("failed to delete mirror password: {}", err);
+                                    }
+                                }
+                            }
+                            return Command::batch([command, self.mirror_status()]);
+                        }
+                        DialogPage::Locks(locks) => {
+                            let stale = locks
+                                .into_iter()
+                                .filter(|lock| lock.stale)
+                                .map(|lock| lock.id)
+                                .collect();
+                            return self.update(Message::RemoveLocks(stale));
+                        }
+                        DialogPage::DeleteSnapshots(snapshots, _) => {
+                            return self.update(Message::Content(
+                                content::Message::DeleteSnapshots(snapshots),
+                            ));
+                        }
+                        DialogPage::TagSnapshots(snapshots, _, tags) => {
+                            return self.update(Message::Content(content::Message::TagSnapshots(
+                                snapshots, tags,
+                            )));
+                        }
+                        DialogPage::ExportSnapshots(snapshots, _, format) => {
+                            return self.update(Message::RequestExportDirectory(snapshots, format));
+                        }
                     }
                 }
             }
@@ -682,17 +2730,38 @@ impl Application for App {
                 self.dialog_pages[0] = dialog_page;
             }
             Message::WindowClose => {
+                if let Some(mount) = self.mount.take() {
+                    mount.unmount();
+                }
+                let Some(content::Command::DeleteSnapshots(repository, password, snapshots)) =
+                    self.content.flush_deletes()
+                else {
+                    return window::close(window::Id::MAIN);
+                };
+                // the window stays responsive until the deletes are done
+                return Command::perform(
+                    async move {
+                        backup::snapshot::delete(&repository, &password, snapshots)
+                            .map_err(|e| e.to_string())
+                    },
+                    |result| cosmic::app::Message::App(Message::DeletesFlushed(result)),
+                );
+            }
+            Message::DeletesFlushed(result) => {
+                if let Err(err) = result {
+                    log::error!("failed to delete snapshots: {}", err);
+                }
                 return window::close(window::Id::MAIN);
             }
             Message::WindowNew => match env::current_exe() {
                 Ok(exe) => match process::Command::new(&exe).spawn() {
                     Ok(_child) => {}
                     Err(err) => {
-                        eprintln!("failed to execute {:?}: {}", exe, err);
+                        log::error!("failed to execute {:?}: {}", exe, err);
                     }
                 },
                 Err(err) => {
-                    eprintln!("failed to get current executable path: {}", err);
+                    log::error!("failed to get current executable path: {}", err);
                 }
             },
             Message::LaunchUrl(url) => match open::that_detached(&url) {
@@ -710,6 +2779,7 @@ impl Application for App {
             }
             Message::Modifiers(modifiers) => {
                 self.modifiers = modifiers;
+                self.content.update(content::Message::Modifiers(modifiers));
             }
             Message::AppTheme(index) => {
                 let app_theme = match index {
@@ -720,6 +2790,45 @@ impl Application for App {
                 config_set!(app_theme, app_theme);
                 return self.update_config();
             }
+            Message::ShowLog => {
+                self.context_page = ContextPage::Log;
+                self.core.window.show_context = true;
+                self.set_context_title(ContextPage::Log.title());
+                self.log = None;
+                return Command::perform(
+                    async { logs::tail().map_err(|err| err.to_string()) },
+                    |log| cosmic::app::Message::App(Message::Log(log)),
+                );
+            }
+            Message::Log(log) => {
+                if let Err(err) = &log {
+                    log::error!("failed to read the log: {}", err);
+                }
+                self.log = Some(log);
+            }
+            Message::StaleDays(index) => {
+                if let Some(days) = STALE_DAYS.get(index) {
+                    config_set!(stale_days, *days);
+                    return self.refresh_health();
+                }
+            }
+            Message::WindowResized(width, height) => {
+                self.window_size = Some((width, height));
+                return Command::perform(tokio::time::sleep(SAVE_WINDOW_SIZE_DELAY), move |_| {
+                    cosmic::app::Message::App(Message::SaveWindowSize(width, height))
+                });
+            }
+            Message::SaveWindowSize(width, height) => {
+                // a later resize saves its own size
+                let size = Some((width, height));
+                if self.window_size == size && self.config.window_size != size {
+                    config_set!(window_size, size);
+                }
+            }
+            Message::LogFile(log_file) => {
+                // the logger is set up once at startup
+                config_set!(log_file, log_file);
+            }
             Message::SystemThemeModeChange(_) => {
                 return self.update_config();
             }
@@ -728,3 +2837,86 @@ impl Application for App {
         Command::none()
     }
 }
+
+/// Tells the desktop a profile's backup finished, with what it added and how long it took.
+fn notify_backup(run: &Run) -> Command<Message> {
+    let name = run.profile.clone().unwrap_or_default();
+    let seconds = (run.finished - run.started).num_seconds();
+    let notification = match &run.error {
+        None => Notification {
+            summary: fl!("profile-finished", name = name.as_str()),
+            body: fl!(
+                "notification-finished",
+                added = ByteSize::b(run.summary.as_ref().map_or(0, |summary| summary.data_added))
+                    .to_string(),
+                seconds = seconds
+            ),
+            urgent: false,
+            actions: run
+                .snapshot
+                .iter()
+                .map(|_| (OPEN_SNAPSHOT.to_string(), fl!("show-snapshot")))
+                .collect(),
+        },
+        Some(error) => Notification {
+            summary: fl!("notification-failed", name = name.as_str()),
+            body: fl!(
+                "notification-failed-body",
+                error = error.as_str(),
+                seconds = seconds
+            ),
+            urgent: true,
+            actions: vec![(SHOW_LOG.to_string(), fl!("show-log"))],
+        },
+    };
+    show_notification(notification, run.repository.clone(), run.snapshot.clone())
+}
+
+/// Tells the desktop a repository was synced to its mirror, or why that failed.
+fn notify_sync(
+    repository: &Repository,
+    duration: Duration,
+    result: &Result<usize, String>,
+) -> Command<Message> {
+    let name = repository.name.as_str();
+    let seconds = duration.as_secs();
+    let notification = match result {
+        Ok(count) => Notification {
+            summary: fl!("notification-synced", name = name),
+            body: fl!("notification-synced-body", count = count, seconds = seconds),
+            ..Default::default()
+        },
+        Err(error) => Notification {
+            summary: fl!("notification-sync-failed", name = name),
+            body: fl!(
+                "notification-failed-body",
+                error = error.as_str(),
+                seconds = seconds
+            ),
+            urgent: true,
+            actions: vec![(SHOW_APP_LOG.to_string(), fl!("show-log"))],
+        },
+    };
+    show_notification(notification, repository.path.clone(), None)
+}
+
+/// Shows `notification` about `repository`, handling the action chosen in it.
+fn show_notification(
+    notification: Notification,
+    repository: PathBuf,
+    snapshot: Option<String>,
+) -> Command<Message> {
+    Command::perform(
+        async move { notifications::show(&notification).await },
+        move |result| match result {
+            Ok(Some(action)) => {
+                cosmic::app::Message::App(Message::NotificationAction(repository, snapshot, action))
+            }
+            Ok(None) => cosmic::app::Message::None,
+            Err(err) => {
+                log::warn!("failed to show a notification: {}", err);
+                cosmic::app::Message::None
+            }
+        },
+    )
+}
@@ -1,6 +1,14 @@
+use chrono::{DateTime, Local};
 use std::path::PathBuf;
 
-use crate::app::App;
+use crate::{
+    app::App,
+    backup::{
+        filter::SnapshotFilter,
+        hooks::Hooks,
+        snapshot::{ChangeDetection, Parent, Source},
+    },
+};
 use cosmic::{
     cosmic_config::{self, cosmic_config_derive::CosmicConfigEntry, Config, CosmicConfigEntry},
     theme, Application,
@@ -13,6 +21,15 @@ pub const CONFIG_VERSION: u64 = 1;
 pub struct StellarshotConfig {
     pub app_theme: AppTheme,
     pub repositories: Vec<Repository>,
+    /// Days after the last backup a repository is shown as stale, 0 for the default.
+    pub stale_days: u32,
+    /// Whether the log is also written to daily rotated files in the state directory.
+    pub log_file: bool,
+    /// The repository opened last, selected again on launch.
+    pub last_repository: Option<PathBuf>,
+    /// The width and height of the main window when it was last resized.
+    pub window_size: Option<(u32, u32)>,
+    pub list_state: ListState,
 }
 
 impl StellarshotConfig {
@@ -37,6 +54,69 @@ impl StellarshotConfig {
 pub struct Repository {
     pub name: String,
     pub path: PathBuf,
+    /// The last filter applied to the snapshot list.
+    #[serde(default)]
+    pub filter: SnapshotFilter,
+    /// Globs of files left out of new snapshots.
+    #[serde(default)]
+    pub excludes: Vec<String>,
+    /// Another repository receiving copies of the snapshots, for an offsite backup.
+    #[serde(default)]
+    pub mirror: Option<Mirror>,
+    /// Saved backups that can be run again with one click.
+    #[serde(default)]
+    pub profiles: Vec<Profile>,
+}
+
+#[derive(Clone, Debug, Default, Eq, PartialEq, Serialize, Deserialize)]
+pub struct Profile {
+    pub name: String,
+    pub source: Source,
+    #[serde(default)]
+    pub hooks: Hooks,
+    #[serde(default)]
+    pub parent: Parent,
+    #[serde(default)]
+    pub detection: ChangeDetection,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub struct Mirror {
+    /// Path of the target repository, one of the configured repositories.
+    pub target: PathBuf,
+    pub policy: MirrorPolicy,
+    pub last_synced: Option<DateTime<Local>>,
+}
+
+/// When new snapshots are copied to the mirror.
+///
+/// Syncing needs the password of the repository, so repositories that are not open are only
+/// synced if their password is saved in the keyring.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, Serialize, Deserialize)]
+pub enum MirrorPolicy {
+    #[default]
+    Manual,
+    AfterBackup,
+    /// Once a day, when the last sync is more than a day ago.
+    Nightly,
+}
+
+impl MirrorPolicy {
+    pub const ALL: [MirrorPolicy; 3] = [
+        MirrorPolicy::Manual,
+        MirrorPolicy::AfterBackup,
+        MirrorPolicy::Nightly,
+    ];
+
+    /// Whether a sync is due at `now`.
+    pub fn is_due(&self, last_synced: Option<DateTime<Local>>, now: DateTime<Local>) -> bool {
+        match self {
+            MirrorPolicy::Nightly => {
+                last_synced.map_or(true, |last| now - last >= chrono::Duration::days(1))
+            }
+            MirrorPolicy::Manual | MirrorPolicy::AfterBackup => false,
+        }
+    }
 }
 
 #[derive(Clone, Copy, Debug, Default, Eq, PartialEq, Serialize, Deserialize)]
@@ -56,3 +136,45 @@ impl AppTheme {
         }
     }
 }
+
+/// How the snapshot and disk usage lists are ordered, kept between launches.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, Serialize, Deserialize)]
+pub struct ListState {
+    pub sort: SortColumn,
+    pub sort_ascending: bool,
+    pub group_by: GroupBy,
+    pub usage_sort: UsageSort,
+}
+
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, Serialize, Deserialize)]
+pub enum SortColumn {
+    #[default]
+    Time,
+    Host,
+    Paths,
+    Tags,
+    Size,
+    Added,
+    Unique,
+}
+
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, Serialize, Deserialize)]
+pub enum GroupBy {
+    #[default]
+    None,
+    Host,
+    Paths,
+    Date,
+}
+
+impl GroupBy {
+    pub const ALL: [GroupBy; 4] = [GroupBy::None, GroupBy::Host, GroupBy::Paths, GroupBy::Date];
+}
+
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, Serialize, Deserialize)]
+pub enum UsageSort {
+    #[default]
+    Size,
+    Files,
+    Name,
+}
@@ -22,6 +22,10 @@ pub fn menu_bar<'a>(key_binds: &HashMap<KeyBind, Action>) -> Element<'a, Message
                 vec![
                     Item::Button(fl!("new-repo"), Action::CreateRepository),
                     Item::Button(fl!("new-snap"), Action::CreateSnapshot),
+                    Item::Divider,
+                    Item::Button(fl!("mount-repo"), Action::MountRepository),
+                    Item::Button(fl!("unmount-repo"), Action::UnmountRepository),
+                    Item::Divider,
                     Item::Button(fl!("new-window"), Action::WindowNew),
                     Item::Button(fl!("quit"), Action::WindowClose),
                 ],
@@ -31,7 +35,13 @@ pub fn menu_bar<'a>(key_binds: &HashMap<KeyBind, Action>) -> Element<'a, Message
             root(fl!("edit")),
             items(
                 key_binds,
-                vec![Item::Button(fl!("delete-repo"), Action::DeleteRepository)],
+                vec![
+                    Item::Button(fl!("exclude-rules-menu"), Action::EditExcludes),
+                    Item::Button(fl!("mirror-menu"), Action::EditMirror),
+                    Item::Button(fl!("repository-locks-menu"), Action::ShowLocks),
+                    Item::Divider,
+                    Item::Button(fl!("delete-repo"), Action::DeleteRepository),
+                ],
             ),
         ),
         Tree::with_children(
@@ -40,6 +50,7 @@ pub fn menu_bar<'a>(key_binds: &HashMap<KeyBind, Action>) -> Element<'a, Message
                 key_binds,
                 vec![
                     Item::Button(fl!("menu-settings"), Action::Settings),
+                    Item::Button(fl!("menu-log"), Action::ShowLog),
                     Item::Divider,
                     Item::Button(fl!("menu-about"), Action::About),
                 ],
@@ -3,8 +3,11 @@ use std::sync::Mutex;
 use super::config::StellarshotConfig;
 use super::icon_cache::{IconCache, ICON_CACHE};
 use crate::app::Flags;
+use crate::core::logs;
 use cosmic::app::Settings;
 use cosmic::iced::{Limits, Size};
+use tracing_appender::rolling::{self, Rotation};
+use tracing_subscriber::fmt::format::FmtSpan;
 use tracing_subscriber::layer::SubscriberExt;
 use tracing_subscriber::{fmt, prelude::*, EnvFilter};
 
@@ -22,17 +25,46 @@ pub fn get_app_settings() -> Settings {
     let mut settings = Settings::default();
     settings = settings.theme(config.app_theme.theme());
     settings = settings.size_limits(Limits::NONE.min_width(400.0).min_height(180.0));
-    settings = settings.size(Size::new(800.0, 800.0));
+    let (width, height) = config.window_size.unwrap_or((800, 800));
+    settings = settings.size(Size::new(width as f32, height as f32));
     settings = settings.debug(false);
     settings
 }
 
 pub fn set_logger() {
-    std::env::set_var("RUST_LOG", "stellarshot=info");
+    // a RUST_LOG set by the user takes precedence
+    let filter =
+        EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new("stellarshot=info"));
+
+    let appender = StellarshotConfig::config().log_file.then(|| {
+        rolling::Builder::new()
+            .rotation(Rotation::DAILY)
+            .filename_prefix(logs::LOG_FILE_PREFIX)
+            .max_log_files(logs::MAX_LOG_FILES)
+            .build(logs::log_dir())
+    });
+    // the error can only be logged once the subscriber is installed
+    let (appender, error) = match appender {
+        Some(Ok(appender)) => (Some(appender), None),
+        Some(Err(err)) => (None, Some(err)),
+        None => (None, None),
+    };
+    let file = appender.map(|appender| {
+        fmt::layer()
+            .with_ansi(false)
+            .with_span_events(FmtSpan::CLOSE)
+            .with_writer(appender)
+    });
+
     tracing_subscriber::registry()
-        .with(fmt::layer())
-        .with(EnvFilter::from_default_env())
+        .with(fmt::layer().with_span_events(FmtSpan::CLOSE))
+        .with(file)
+        .with(filter)
         .init();
+
+    if let Some(err) = error {
+        tracing::warn!(%err, "failed to open the log file");
+    }
 }
 
 pub fn set_icon_cache() {
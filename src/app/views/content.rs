@@ -1,22 +1,222 @@
+use bytesize::ByteSize;
+use chrono::{DateTime, Local, NaiveDate};
 use cosmic::{
     iced::{
         alignment::{Horizontal, Vertical},
         Alignment, Length,
     },
+    iced_core::keyboard::Modifiers,
     theme, widget, Apply, Element,
 };
-use rustic_core::{repofile::SnapshotFile, Id};
+use rustic_core::{repofile::SnapshotFile, Id, StringList};
+use std::cmp::Ordering;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
 
 use crate::{
-    app::{config::Repository, icon_cache::IconCache},
-    backup::snapshot::fetch,
+    app::{
+        config::{GroupBy, ListState, Mirror, Profile, Repository, SortColumn, UsageSort},
+        icon_cache::IconCache,
+    },
+    backup::{
+        cost::SnapshotCost,
+        diff::{Change, ChangeKind},
+        filter::SnapshotFilter,
+        find::{Match, Version},
+        history::{Operation, Run, RunSummary},
+        hooks::{Hooks, DEFAULT_TIMEOUT},
+        preview::{Preview, TEXT_LIMIT},
+        snapshot::{fetch, ChangeDetection, Parent, Source},
+        stats::Statistics,
+        usage::DirUsage,
+    },
     fl,
 };
 
+/// Width reserved for the buttons at the end of each snapshot row.
+const ACTIONS_WIDTH: f32 = 400.0;
+
+/// Deleted snapshots are hidden for this long before they are removed, so the deletion can be
+/// undone.
+pub const DELETE_GRACE_PERIOD: Duration = Duration::from_secs(10);
+
+/// Filter edits are saved once the filter has not changed for this long, not on every keystroke.
+pub const FILTER_SAVE_DELAY: Duration = Duration::from_secs(1);
+
+/// The growth chart shows this many of the latest days with snapshots.
+const GROWTH_DAYS: usize = 30;
+
+/// Diffs and search results can contain every file of a repository, only this many rows are shown.
+const MAX_ROWS: usize = 1000;
+
 pub struct Content {
     pub repository: Option<Repository>,
     snapshots: Option<Vec<SnapshotFile>>,
     pub(crate) password: String,
+    page: Page,
+    compare: Option<Id>,
+    query: String,
+    preview: Option<FilePreview>,
+    /// The mounted repository and its mountpoint.
+    pub(crate) mount: Option<(PathBuf, PathBuf)>,
+    sort: SortColumn,
+    sort_ascending: bool,
+    group_by: GroupBy,
+    group_options: Vec<String>,
+    selected: HashSet<Id>,
+    /// The snapshot a shift-click selects from.
+    anchor: Option<Id>,
+    modifiers: Modifiers,
+    /// Snapshots waiting for their grace period to end, by deletion.
+    pending_deletes: BTreeMap<u64, Vec<Id>>,
+    next_delete: u64,
+    costs: Option<Result<HashMap<Id, SnapshotCost>, String>>,
+    /// The snapshots the costs are computed for, they are kept while the snapshots stay the same.
+    cost_ids: HashSet<Id>,
+    usage_sort: UsageSort,
+    /// The name of the mirror and the number of snapshots not copied to it yet.
+    mirror_status: Option<(String, Result<usize, String>)>,
+    pub(crate) syncing: bool,
+    source_options: Vec<String>,
+    parent_options: Vec<String>,
+    /// The name of the profile being backed up.
+    pub(crate) running: Option<String>,
+    /// The check or prune in progress.
+    pub(crate) maintenance: Option<Operation>,
+    /// Counts the filter edits, so only the last one of a burst is saved.
+    filter_edits: u64,
+    /// Whether the filter has edits not saved yet.
+    filter_unsaved: bool,
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum FilterField {
+    Hosts,
+    Tags,
+    Paths,
+    After,
+    Before,
+    Text,
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum ProfileField {
+    Name,
+    Paths,
+    Command,
+    Filename,
+    PreHook,
+    PostHook,
+    FailureHook,
+    Timeout,
+    ParentSnapshot,
+}
+
+struct FilePreview {
+    name: String,
+    snap_path: String,
+    content: Option<Result<Preview, String>>,
+    image: Option<widget::image::Handle>,
+}
+
+/// A profile being written on the profiles page.
+#[derive(Default)]
+struct ProfileForm {
+    name: String,
+    command: bool,
+    /// Comma separated paths.
+    paths: String,
+    command_line: String,
+    filename: String,
+    pre_hook: String,
+    post_hook: String,
+    failure_hook: String,
+    /// Seconds, empty for the default.
+    timeout: String,
+    /// The chosen entry of the parent dropdown.
+    parent: usize,
+    parent_snapshot: String,
+    detection: ChangeDetection,
+}
+
+impl ProfileForm {
+    fn profile(&self) -> Option<Profile> {
+        let source = match self.command {
+            false => {
+                let paths: Vec<PathBuf> = self
+                    .paths
+                    .split(',')
+                    .map(str::trim)
+                    .filter(|path| !path.is_empty())
+                    .map(PathBuf::from)
+                    .collect();
+                (!paths.is_empty()).then_some(Source::Paths(paths))?
+            }
+            true => (!self.command_line.trim().is_empty() && !self.filename.trim().is_empty())
+                .then(|| Source::Command {
+                    command: self.command_line.trim().to_string(),
+                    filename: self.filename.trim().to_string(),
+                })?,
+        };
+        let timeout = match self.timeout.trim() {
+            "" => 0,
+            timeout => timeout.parse().ok()?,
+        };
+        let hooks = Hooks {
+            pre: self.pre_hook.trim().to_string(),
+            post: self.post_hook.trim().to_string(),
+            failure: self.failure_hook.trim().to_string(),
+            timeout,
+        };
+        let parent = match self.parent {
+            1 => {
+                let id = self.parent_snapshot.trim();
+                (!id.is_empty()).then(|| Parent::Snapshot(id.to_string()))?
+            }
+            2 => Parent::Rescan,
+            _ => Parent::Latest,
+        };
+        let name = self.name.trim();
+        (!name.is_empty()).then(|| Profile {
+            name: name.to_string(),
+            source,
+            hooks,
+            parent,
+            detection: self.detection,
+        })
+    }
+}
+
+enum Page {
+    Snapshots,
+    Diff {
+        title: String,
+        live: bool,
+        changes: Option<Result<Vec<Change>, String>>,
+    },
+    Search {
+        query: String,
+        matches: Option<Result<Vec<Match>, String>>,
+    },
+    History {
+        path: PathBuf,
+        versions: Option<Result<Vec<Version>, String>>,
+    },
+    Statistics(Option<Result<Statistics, String>>),
+    Profiles(ProfileForm),
+    Runs {
+        runs: Option<Result<Vec<Run>, String>>,
+        /// The run whose log is shown.
+        expanded: Option<usize>,
+    },
+    Details(Id),
+    Usage {
+        snapshot: Id,
+        usage: Option<Result<DirUsage, String>>,
+        /// Indices into the nested `dirs` leading to the shown directory.
+        path: Vec<usize>,
+    },
 }
 
 #[derive(Debug, Clone)]
@@ -24,21 +224,152 @@ pub enum Message {
     SetRepository(Repository, String),
     SetSnapshots(Vec<SnapshotFile>),
     ReloadSnapshots,
-    Delete(Id, String),
+    Delete(Id),
     Select(Id),
+    Compare(Id),
+    CompareLive(Id),
+    SetDiff(Result<Vec<Change>, String>),
+    SearchInput(String),
+    Search,
+    SetMatches(Result<Vec<Match>, String>),
+    History(PathBuf),
+    SetVersions(Result<Vec<Version>, String>),
+    Restore(String),
+    Preview(String, String),
+    SetPreview(Result<Preview, String>),
+    SaveFile(String, String),
+    Unmount,
+    Export(Id),
+    SortBy(SortColumn),
+    SetGroupBy(usize),
+    Filter(FilterField, String),
+    ClearFilter,
+    SaveFilter(u64),
+    Modifiers(Modifiers),
+    ToggleSelected(Id),
+    SelectAll,
+    ClearSelection,
+    BulkDelete,
+    BulkTag,
+    BulkExport,
+    BulkCopy,
+    DeleteSnapshots(Vec<Id>),
+    TagSnapshots(Vec<Id>, String),
+    UndoDelete(u64),
+    DeleteExpired(u64),
+    Statistics,
+    SetStatistics(Result<Statistics, String>),
+    SetCosts(Result<HashMap<Id, SnapshotCost>, String>),
+    Usage(Id),
+    SetUsage(Result<DirUsage, String>),
+    OpenDir(usize),
+    ParentDir,
+    UsageSortBy(UsageSort),
+    Exclude(PathBuf),
+    SetExcludes(Vec<String>),
+    SetMirror(Option<Mirror>),
+    SetMirrorStatus(String, Result<usize, String>),
+    Sync,
+    Profiles,
+    ProfileInput(ProfileField, String),
+    ProfileSource(usize),
+    ProfileParent(usize),
+    ProfileIgnoreCtime(bool),
+    ProfileIgnoreInode(bool),
+    AddProfile,
+    RemoveProfile(usize),
+    RunProfile(usize),
+    Runs,
+    SetRuns(Result<Vec<Run>, String>),
+    ToggleRunLog(usize),
+    Maintain(Operation),
+    ExportRuns,
+    Back,
 }
 
 pub enum Command {
     FetchSnapshots(String, String),
     DeleteSnapshots(String, String, Vec<rustic_core::Id>),
+    DiffSnapshots(String, String, Id, Id),
+    DiffLive(String, String, Id),
+    Find(String, String, String),
+    Versions(String, String, PathBuf),
+    Restore(String, String, String),
+    Preview(String, String, String),
+    SaveFile(String, String, String, String),
+    Unmount,
+    Export(Id),
+    UpdateRepository(Repository),
+    /// Saves the filter after [`FILTER_SAVE_DELAY`] unless it was edited again.
+    SaveFilterLater(u64),
+    SaveListState(ListState),
+    /// Asks for confirmation before acting on the given snapshots with a total size.
+    ConfirmDelete(Vec<Id>, u64),
+    ConfirmTag(Vec<Id>, u64),
+    ConfirmExport(Vec<Id>, u64),
+    /// Asks for the repository to copy the given snapshots to.
+    ConfirmCopy(Vec<Id>),
+    TagSnapshots(String, String, Vec<Id>, Vec<String>),
+    /// Offers to undo the deletion of this many snapshots until the grace period ends.
+    ScheduleDelete(u64, usize),
+    Statistics(String, String),
+    Costs(String, String),
+    Usage(String, String, Id),
+    /// Opens the exclude rules of the repository, with a new rule to add.
+    EditExcludes(Option<String>),
+    /// Copies the new snapshots to the mirror of the repository.
+    Sync,
+    RunProfile(Profile),
+    Runs(PathBuf),
+    /// Checks or prunes the repository.
+    Maintain(Operation),
+    ExportRuns(Vec<Run>),
 }
 
 impl Content {
-    pub fn new() -> Self {
+    pub fn new(list: ListState) -> Self {
         Self {
             repository: None,
             snapshots: None,
             password: String::new(),
+            page: Page::Snapshots,
+            compare: None,
+            query: String::new(),
+            preview: None,
+            mount: None,
+            sort: list.sort,
+            sort_ascending: list.sort_ascending,
+            group_by: list.group_by,
+            group_options: vec![fl!("group-none"), fl!("host"), fl!("paths"), fl!("date")],
+            selected: HashSet::new(),
+            anchor: None,
+            modifiers: Modifiers::empty(),
+            pending_deletes: BTreeMap::new(),
+            next_delete: 0,
+            costs: None,
+            cost_ids: HashSet::new(),
+            usage_sort: list.usage_sort,
+            mirror_status: None,
+            syncing: false,
+            source_options: vec![fl!("source-paths"), fl!("source-command")],
+            parent_options: vec![
+                fl!("parent-latest"),
+                fl!("parent-snapshot"),
+                fl!("parent-rescan"),
+            ],
+            running: None,
+            maintenance: None,
+            filter_edits: 0,
+            filter_unsaved: false,
+        }
+    }
+
+    fn list_state(&self) -> ListState {
+        ListState {
+            sort: self.sort,
+            sort_ascending: self.sort_ascending,
+            group_by: self.group_by,
+            usage_sort: self.usage_sort,
         }
     }
 
@@ -71,8 +402,28 @@ impl Content {
             .into();
         };
 
+        let page = match &self.page {
+            Page::Snapshots => self.list_view(repository),
+            Page::Diff {
+                title,
+                live,
+                changes,
+            } => self.diff_view(title, *live, changes.as_ref()),
+            Page::Search { query, matches } => self.search_view(query, matches.as_ref()),
+            Page::History { path, versions } => self.history_view(path, versions.as_ref()),
+            Page::Statistics(statistics) => self.statistics_view(repository, statistics.as_ref()),
+            Page::Profiles(form) => self.profiles_view(repository, form),
+            Page::Runs { runs, expanded } => self.runs_view(repository, runs.as_ref(), *expanded),
+            Page::Details(id) => self.details_view(*id),
+            Page::Usage {
+                snapshot,
+                usage,
+                path,
+            } => self.usage_view(*snapshot, usage.as_ref(), path),
+        };
+
         widget::column::with_capacity(2)
-            .push(self.list_view(repository))
+            .push(page)
             .spacing(spacing.space_xxs)
             .apply(widget::container)
             .height(Length::Fill)
@@ -84,26 +435,466 @@ impl Content {
         let mut commands = vec![];
         match message {
             Message::SetRepository(repository, password) => {
+                if let Some(previous) = self.repository.take().filter(|_| self.filter_unsaved) {
+                    self.filter_unsaved = false;
+                    commands.push(Command::UpdateRepository(previous));
+                }
                 self.password = password;
                 self.snapshots = None;
+                self.page = Page::Snapshots;
+                self.compare = None;
+                self.query.clear();
+                self.preview = None;
+                self.selected.clear();
+                self.anchor = None;
+                self.mirror_status = None;
                 self.repository = Some(repository.clone());
                 let path = repository.path.display().to_string();
                 commands.push(Command::FetchSnapshots(path, self.password.clone()))
             }
-            Message::SetSnapshots(snapshots) => self.snapshots = Some(snapshots),
-            Message::Delete(id, password) => {
-                let path = self.repository.as_ref().unwrap().path.display().to_string();
-                commands.push(Command::DeleteSnapshots(path, password, vec![id]))
+            Message::SetSnapshots(snapshots) => {
+                self.selected
+                    .retain(|id| snapshots.iter().any(|snapshot| snapshot.id == *id));
+                // the costs walk every tree, so they are only computed again when snapshots were
+                // added or removed, which changes what each snapshot shares with the others
+                let ids: HashSet<Id> = snapshots.iter().map(|snapshot| snapshot.id).collect();
+                if ids != self.cost_ids {
+                    self.cost_ids = ids;
+                    self.costs = None;
+                    let path = self.repository.as_ref().unwrap().path.display().to_string();
+                    commands.push(Command::Costs(path, self.password.clone()))
+                }
+                self.snapshots = Some(snapshots);
             }
-            Message::Select(_) => todo!(),
+            Message::SetCosts(costs) => {
+                if costs.is_err() {
+                    // computed again on the next reload
+                    self.cost_ids.clear();
+                }
+                self.costs = Some(costs);
+            }
+            Message::Delete(id) => {
+                let size = self.total_size(&[id]);
+                commands.push(Command::ConfirmDelete(vec![id], size));
+            }
+            Message::Select(id) => self.page = Page::Details(id),
             Message::ReloadSnapshots => {
                 let path = self.repository.as_ref().unwrap().path.display().to_string();
-                commands.push(Command::FetchSnapshots(path, "password".into()))
+                commands.push(Command::FetchSnapshots(path, self.password.clone()))
+            }
+            Message::Compare(id) => match self.compare.take() {
+                Some(base) if base != id => {
+                    let (from, to) = self.chronological(base, id);
+                    self.page = Page::Diff {
+                        title: fl!("diff-title", from = from.to_string(), to = to.to_string()),
+                        live: false,
+                        changes: None,
+                    };
+                    let path = self.repository.as_ref().unwrap().path.display().to_string();
+                    commands.push(Command::DiffSnapshots(
+                        path,
+                        self.password.clone(),
+                        from,
+                        to,
+                    ))
+                }
+                Some(_) => {}
+                None => self.compare = Some(id),
+            },
+            Message::CompareLive(id) => {
+                self.compare = None;
+                self.page = Page::Diff {
+                    title: fl!("diff-live-title", id = id.to_string()),
+                    live: true,
+                    changes: None,
+                };
+                let path = self.repository.as_ref().unwrap().path.display().to_string();
+                commands.push(Command::DiffLive(path, self.password.clone(), id))
+            }
+            Message::SetDiff(result) => {
+                if let Page::Diff { changes, .. } = &mut self.page {
+                    *changes = Some(result);
+                }
+            }
+            Message::SearchInput(query) => self.query = query,
+            Message::Search => {
+                if !self.query.is_empty() {
+                    self.page = Page::Search {
+                        query: self.query.clone(),
+                        matches: None,
+                    };
+                    let path = self.repository.as_ref().unwrap().path.display().to_string();
+                    commands.push(Command::Find(
+                        path,
+                        self.password.clone(),
+                        self.query.clone(),
+                    ))
+                }
+            }
+            Message::SetMatches(result) => {
+                if let Page::Search { matches, .. } = &mut self.page {
+                    *matches = Some(result);
+                }
+            }
+            Message::History(file) => {
+                self.page = Page::History {
+                    path: file.clone(),
+                    versions: None,
+                };
+                let path = self.repository.as_ref().unwrap().path.display().to_string();
+                commands.push(Command::Versions(path, self.password.clone(), file))
+            }
+            Message::SetVersions(result) => {
+                if let Page::History { versions, .. } = &mut self.page {
+                    *versions = Some(result);
+                }
+            }
+            Message::Restore(snap_path) => {
+                let path = self.repository.as_ref().unwrap().path.display().to_string();
+                commands.push(Command::Restore(path, self.password.clone(), snap_path))
+            }
+            Message::Preview(snap_path, name) => {
+                self.preview = Some(FilePreview {
+                    name,
+                    snap_path: snap_path.clone(),
+                    content: None,
+                    image: None,
+                });
+                let path = self.repository.as_ref().unwrap().path.display().to_string();
+                commands.push(Command::Preview(path, self.password.clone(), snap_path))
+            }
+            Message::SetPreview(result) => {
+                if let Some(preview) = &mut self.preview {
+                    if let Ok(Preview::Image(data)) = &result {
+                        preview.image = Some(widget::image::Handle::from_memory(data.clone()));
+                    }
+                    preview.content = Some(result);
+                }
+            }
+            Message::SaveFile(snap_path, name) => {
+                let path = self.repository.as_ref().unwrap().path.display().to_string();
+                commands.push(Command::SaveFile(
+                    path,
+                    self.password.clone(),
+                    snap_path,
+                    name,
+                ))
+            }
+            Message::Unmount => commands.push(Command::Unmount),
+            Message::Export(id) => commands.push(Command::Export(id)),
+            Message::Modifiers(modifiers) => self.modifiers = modifiers,
+            Message::ToggleSelected(id) => {
+                let visible = self.visible();
+                let range = match self.anchor {
+                    Some(anchor) if self.modifiers.shift() => {
+                        let start = visible.iter().position(|v| *v == anchor);
+                        let end = visible.iter().position(|v| *v == id);
+                        start
+                            .zip(end)
+                            .map(|(start, end)| start.min(end)..=start.max(end))
+                    }
+                    _ => None,
+                };
+                match range {
+                    Some(range) => self.selected.extend(&visible[range]),
+                    None => {
+                        if !self.selected.remove(&id) {
+                            self.selected.insert(id);
+                        }
+                        self.anchor = Some(id);
+                    }
+                }
+            }
+            Message::SelectAll => {
+                let visible = self.visible();
+                self.selected.extend(visible);
+            }
+            Message::ClearSelection => {
+                self.selected.clear();
+                self.anchor = None;
             }
+            Message::BulkDelete => {
+                let (ids, size) = self.selection();
+                commands.push(Command::ConfirmDelete(ids, size));
+            }
+            Message::BulkTag => {
+                let (ids, size) = self.selection();
+                commands.push(Command::ConfirmTag(ids, size));
+            }
+            Message::BulkExport => {
+                let (ids, size) = self.selection();
+                commands.push(Command::ConfirmExport(ids, size));
+            }
+            Message::BulkCopy => {
+                let (ids, _) = self.selection();
+                commands.push(Command::ConfirmCopy(ids));
+            }
+            Message::DeleteSnapshots(ids) => {
+                self.selected.clear();
+                self.next_delete += 1;
+                let count = ids.len();
+                self.pending_deletes.insert(self.next_delete, ids);
+                commands.push(Command::ScheduleDelete(self.next_delete, count));
+            }
+            Message::UndoDelete(token) => {
+                self.pending_deletes.remove(&token);
+            }
+            Message::DeleteExpired(token) => {
+                // undone deletions and those flushed early are gone already
+                if let Some(ids) = self.pending_deletes.remove(&token) {
+                    let path = self.repository.as_ref().unwrap().path.display().to_string();
+                    commands.push(Command::DeleteSnapshots(path, self.password.clone(), ids))
+                }
+            }
+            Message::TagSnapshots(ids, tags) => {
+                let tags = tags
+                    .split(',')
+                    .map(str::trim)
+                    .filter(|tag| !tag.is_empty())
+                    .map(String::from)
+                    .collect::<Vec<_>>();
+                if !tags.is_empty() {
+                    self.selected.clear();
+                    let path = self.repository.as_ref().unwrap().path.display().to_string();
+                    commands.push(Command::TagSnapshots(
+                        path,
+                        self.password.clone(),
+                        ids,
+                        tags,
+                    ))
+                }
+            }
+            Message::SortBy(column) => {
+                if self.sort == column {
+                    self.sort_ascending = !self.sort_ascending;
+                } else {
+                    self.sort = column;
+                    // text sorts alphabetically, times and sizes largest first
+                    self.sort_ascending = matches!(
+                        column,
+                        SortColumn::Host | SortColumn::Paths | SortColumn::Tags
+                    );
+                }
+                commands.push(Command::SaveListState(self.list_state()));
+            }
+            Message::SetGroupBy(index) => {
+                self.group_by = GroupBy::ALL[index];
+                commands.push(Command::SaveListState(self.list_state()));
+            }
+            Message::Filter(field, value) => {
+                if let Some(repository) = self.repository.as_mut() {
+                    let filter = &mut repository.filter;
+                    match field {
+                        FilterField::Hosts => filter.hosts = value,
+                        FilterField::Tags => filter.tags = value,
+                        FilterField::Paths => filter.paths = value,
+                        FilterField::After => filter.after = value,
+                        FilterField::Before => filter.before = value,
+                        FilterField::Text => filter.text = value,
+                    }
+                    self.filter_edits += 1;
+                    self.filter_unsaved = true;
+                    commands.push(Command::SaveFilterLater(self.filter_edits));
+                }
+            }
+            Message::SaveFilter(edit) => {
+                if edit == self.filter_edits && self.filter_unsaved {
+                    if let Some(repository) = &self.repository {
+                        self.filter_unsaved = false;
+                        commands.push(Command::UpdateRepository(repository.clone()));
+                    }
+                }
+            }
+            Message::ClearFilter => {
+                if let Some(repository) = self.repository.as_mut() {
+                    repository.filter = SnapshotFilter::default();
+                    self.filter_unsaved = false;
+                    commands.push(Command::UpdateRepository(repository.clone()));
+                }
+            }
+            Message::Statistics => {
+                self.page = Page::Statistics(None);
+                let path = self.repository.as_ref().unwrap().path.display().to_string();
+                commands.push(Command::Statistics(path, self.password.clone()))
+            }
+            Message::SetStatistics(result) => {
+                if let Page::Statistics(statistics) = &mut self.page {
+                    *statistics = Some(result);
+                }
+            }
+            Message::Usage(id) => {
+                self.page = Page::Usage {
+                    snapshot: id,
+                    usage: None,
+                    path: Vec::new(),
+                };
+                let path = self.repository.as_ref().unwrap().path.display().to_string();
+                commands.push(Command::Usage(path, self.password.clone(), id))
+            }
+            Message::SetUsage(result) => {
+                if let Page::Usage { usage, .. } = &mut self.page {
+                    *usage = Some(result);
+                }
+            }
+            Message::OpenDir(index) => {
+                if let Page::Usage { path, .. } = &mut self.page {
+                    path.push(index);
+                }
+            }
+            Message::ParentDir => {
+                if let Page::Usage { path, .. } = &mut self.page {
+                    path.pop();
+                }
+            }
+            Message::UsageSortBy(sort) => {
+                self.usage_sort = sort;
+                commands.push(Command::SaveListState(self.list_state()));
+            }
+            Message::Exclude(path) => {
+                commands.push(Command::EditExcludes(Some(path.display().to_string())))
+            }
+            Message::SetExcludes(excludes) => {
+                if let Some(repository) = self.repository.as_mut() {
+                    repository.excludes = excludes;
+                    commands.push(Command::UpdateRepository(repository.clone()));
+                }
+            }
+            Message::SetMirror(mirror) => {
+                if let Some(repository) = self.repository.as_mut() {
+                    if mirror.is_none() {
+                        self.mirror_status = None;
+                    }
+                    repository.mirror = mirror;
+                    commands.push(Command::UpdateRepository(repository.clone()));
+                }
+            }
+            Message::SetMirrorStatus(target, pending) => {
+                self.mirror_status = Some((target, pending));
+            }
+            Message::Sync => commands.push(Command::Sync),
+            Message::Profiles => self.page = Page::Profiles(ProfileForm::default()),
+            Message::ProfileInput(field, value) => {
+                if let Page::Profiles(form) = &mut self.page {
+                    match field {
+                        ProfileField::Name => form.name = value,
+                        ProfileField::Paths => form.paths = value,
+                        ProfileField::Command => form.command_line = value,
+                        ProfileField::Filename => form.filename = value,
+                        ProfileField::PreHook => form.pre_hook = value,
+                        ProfileField::PostHook => form.post_hook = value,
+                        ProfileField::FailureHook => form.failure_hook = value,
+                        ProfileField::Timeout => form.timeout = value,
+                        ProfileField::ParentSnapshot => form.parent_snapshot = value,
+                    }
+                }
+            }
+            Message::ProfileSource(index) => {
+                if let Page::Profiles(form) = &mut self.page {
+                    form.command = index == 1;
+                }
+            }
+            Message::ProfileParent(index) => {
+                if let Page::Profiles(form) = &mut self.page {
+                    form.parent = index;
+                }
+            }
+            Message::ProfileIgnoreCtime(ignore) => {
+                if let Page::Profiles(form) = &mut self.page {
+                    form.detection.ignore_ctime = ignore;
+                }
+            }
+            Message::ProfileIgnoreInode(ignore) => {
+                if let Page::Profiles(form) = &mut self.page {
+                    form.detection.ignore_inode = ignore;
+                }
+            }
+            Message::AddProfile => {
+                let Page::Profiles(form) = &mut self.page else {
+                    return commands;
+                };
+                if let (Some(profile), Some(repository)) =
+                    (form.profile(), self.repository.as_mut())
+                {
+                    *form = ProfileForm::default();
+                    repository.profiles.push(profile);
+                    commands.push(Command::UpdateRepository(repository.clone()));
+                }
+            }
+            Message::RemoveProfile(index) => {
+                if let Some(repository) = self.repository.as_mut() {
+                    if index < repository.profiles.len() {
+                        repository.profiles.remove(index);
+                        commands.push(Command::UpdateRepository(repository.clone()));
+                    }
+                }
+            }
+            Message::RunProfile(index) => {
+                let profile = self
+                    .repository
+                    .as_ref()
+                    .and_then(|repository| repository.profiles.get(index));
+                if let Some(profile) = profile.filter(|_| self.running.is_none()) {
+                    self.running = Some(profile.name.clone());
+                    commands.push(Command::RunProfile(profile.clone()));
+                }
+            }
+            Message::Runs => {
+                // keep the page as it is while reloading after a check or prune
+                if !matches!(self.page, Page::Runs { .. }) {
+                    self.page = Page::Runs {
+                        runs: None,
+                        expanded: None,
+                    };
+                }
+                let path = self.repository.as_ref().unwrap().path.clone();
+                commands.push(Command::Runs(path));
+            }
+            Message::SetRuns(result) => {
+                if let Page::Runs { runs, .. } = &mut self.page {
+                    *runs = Some(result);
+                }
+            }
+            Message::ToggleRunLog(index) => {
+                if let Page::Runs { expanded, .. } = &mut self.page {
+                    *expanded = (*expanded != Some(index)).then_some(index);
+                }
+            }
+            Message::Maintain(operation) => {
+                if self.maintenance.is_none() {
+                    self.maintenance = Some(operation);
+                    commands.push(Command::Maintain(operation));
+                }
+            }
+            Message::ExportRuns => {
+                if let Page::Runs {
+                    runs: Some(Ok(runs)),
+                    ..
+                } = &self.page
+                {
+                    commands.push(Command::ExportRuns(runs.clone()));
+                }
+            }
+            Message::Back => self.page = Page::Snapshots,
         }
         commands
     }
 
+    /// Orders two snapshots so that the older one comes first.
+    fn chronological(&self, a: Id, b: Id) -> (Id, Id) {
+        let time = |id: Id| {
+            self.snapshots
+                .iter()
+                .flatten()
+                .find(|snapshot| snapshot.id == id)
+                .map(|snapshot| snapshot.time)
+        };
+        if time(b) < time(a) {
+            (b, a)
+        } else {
+            (a, b)
+        }
+    }
+
     pub fn list_view<'a>(&'a self, repository: &'a Repository) -> Element<'a, Message> {
         let spacing = theme::active().cosmic().spacing;
 
@@ -115,37 +906,44 @@ impl Content {
             return self.empty(repository);
         }
 
-        let mut section = widget::settings::view_section(fl!("snapshots"));
+        let hint = self
+            .compare
+            .map(|id| widget::text::body(fl!("select-second-snapshot", id = id.to_string())));
 
-        for item in snapshots {
-            let delete_button = widget::button(IconCache::get("user-trash-full-symbolic", 18))
-                .padding(spacing.space_xxs)
-                .style(theme::Button::Destructive)
-                .on_press(Message::Delete(item.id, self.password.clone()));
+        let toolbar = widget::row::with_capacity(2)
+            .align_items(Alignment::Center)
+            .spacing(spacing.space_xxs)
+            .push(widget::text::body(fl!("group-by")))
+            .push(widget::dropdown(
+                &self.group_options,
+                GroupBy::ALL.iter().position(|g| *g == self.group_by),
+                Message::SetGroupBy,
+            ));
 
-            let _details_button = widget::button(IconCache::get("info-outline-symbolic", 18))
-                .padding(spacing.space_xxs)
-                .style(theme::Button::Standard)
-                .on_press(Message::Select(item.id));
+        let mut column = widget::column::with_capacity(4)
+            .spacing(spacing.space_xxs)
+            .padding(spacing.space_xxs)
+            .push(self.repository_header(repository))
+            .push_maybe(hint)
+            .push(self.filter_bar(&repository.filter, snapshots))
+            .push(toolbar)
+            .push_maybe(self.selection_bar())
+            .push(self.columns_header());
 
-            let row = widget::settings::item(
-                item.id.to_string(),
-                widget::row::with_capacity(4)
-                    .align_items(Alignment::Center)
-                    .spacing(spacing.space_xxs)
-                    .padding([spacing.space_xxxs, spacing.space_xxs])
-                    // .push(details_button)
-                    .push(delete_button),
-            );
+        let groups = self.groups(snapshots, &repository.filter);
+        if groups.is_empty() {
+            column = column.push(widget::text::body(fl!("no-matching-snapshots")));
+        }
 
-            section = section.add(row);
+        for (title, items) in groups {
+            let mut section = widget::settings::view_section(title);
+            for item in items {
+                section = section.add(self.snapshot_row(item));
+            }
+            column = column.push(section);
         }
 
-        widget::column::with_capacity(2)
-            .spacing(spacing.space_xxs)
-            .padding(spacing.space_xxs)
-            .push(self.repository_header(repository))
-            .push(section)
+        column
             .apply(widget::container)
             .height(Length::Shrink)
             .apply(widget::scrollable)
@@ -153,6 +951,316 @@ impl Content {
             .into()
     }
 
+    fn filter_bar<'a>(
+        &'a self,
+        filter: &'a SnapshotFilter,
+        snapshots: &[SnapshotFile],
+    ) -> Element<'a, Message> {
+        let spacing = theme::active().cosmic().spacing;
+
+        let input = |placeholder: String, value: &'a str, field: FilterField| {
+            widget::text_input(placeholder, value)
+                .on_input(move |value| Message::Filter(field, value))
+                .width(Length::FillPortion(2))
+        };
+
+        let inputs = widget::row::with_capacity(6)
+            .spacing(spacing.space_xxs)
+            .push(input(fl!("filter-text"), &filter.text, FilterField::Text))
+            .push(input(
+                fl!("filter-hosts"),
+                &filter.hosts,
+                FilterField::Hosts,
+            ))
+            .push(input(fl!("filter-tags"), &filter.tags, FilterField::Tags))
+            .push(input(
+                fl!("filter-paths"),
+                &filter.paths,
+                FilterField::Paths,
+            ))
+            .push(input(
+                fl!("filter-after"),
+                &filter.after,
+                FilterField::After,
+            ))
+            .push(input(
+                fl!("filter-before"),
+                &filter.before,
+                FilterField::Before,
+            ));
+
+        let status = (!filter.is_empty()).then(|| {
+            let shown = snapshots.iter().filter(|s| filter.matches(s)).count();
+            let mut status = widget::row::with_capacity(3)
+                .align_items(Alignment::Center)
+                .spacing(spacing.space_xxs)
+                .push(widget::text::caption(fl!(
+                    "filtered-snapshots",
+                    shown = shown,
+                    total = snapshots.len()
+                )));
+            if !filter.is_valid() {
+                status = status.push(widget::text::caption(fl!("invalid-date")));
+            }
+            status.push(widget::button::text(fl!("clear-filter")).on_press(Message::ClearFilter))
+        });
+
+        widget::column::with_capacity(2)
+            .spacing(spacing.space_xxs)
+            .push(inputs)
+            .push_maybe(status)
+            .into()
+    }
+
+    fn selection_bar(&self) -> Option<Element<Message>> {
+        let spacing = theme::active().cosmic().spacing;
+
+        if self.selected.is_empty() {
+            return None;
+        }
+
+        let (ids, size) = self.selection();
+        let bar = widget::row::with_capacity(6)
+            .align_items(Alignment::Center)
+            .spacing(spacing.space_xxs)
+            .push(
+                widget::text::body(fl!(
+                    "selected-snapshots",
+                    count = ids.len(),
+                    size = ByteSize::b(size).to_string()
+                ))
+                .width(Length::Fill),
+            )
+            .push(widget::button::standard(fl!("add-tags")).on_press(Message::BulkTag))
+            .push(widget::button::standard(fl!("export")).on_press(Message::BulkExport))
+            .push(widget::button::standard(fl!("copy-to-repository")).on_press(Message::BulkCopy))
+            .push(widget::button::destructive(fl!("delete")).on_press(Message::BulkDelete))
+            .push(widget::button::text(fl!("clear-selection")).on_press(Message::ClearSelection));
+
+        Some(bar.into())
+    }
+
+    /// The ids of the snapshots shown in the list, in the order they are shown.
+    fn visible(&self) -> Vec<Id> {
+        match (&self.snapshots, &self.repository) {
+            (Some(snapshots), Some(repository)) => self
+                .groups(snapshots, &repository.filter)
+                .into_iter()
+                .flat_map(|(_, items)| items)
+                .map(|snapshot| snapshot.id)
+                .collect(),
+            _ => Vec::new(),
+        }
+    }
+
+    /// The selected snapshots, oldest first, and the sum of their sizes.
+    fn selection(&self) -> (Vec<Id>, u64) {
+        let mut selected: Vec<&SnapshotFile> = self
+            .snapshots
+            .iter()
+            .flatten()
+            .filter(|snapshot| self.selected.contains(&snapshot.id))
+            .collect();
+        selected.sort_by_key(|snapshot| snapshot.time);
+
+        let ids: Vec<Id> = selected.iter().map(|snapshot| snapshot.id).collect();
+        let size = self.total_size(&ids);
+        (ids, size)
+    }
+
+    fn total_size(&self, ids: &[Id]) -> u64 {
+        self.snapshots
+            .iter()
+            .flatten()
+            .filter(|snapshot| ids.contains(&snapshot.id))
+            .filter_map(|snapshot| snapshot.summary.as_ref())
+            .map(|summary| summary.total_bytes_processed)
+            .sum()
+    }
+
+    /// Ends the grace period of all pending deletions, returning the command deleting them.
+    ///
+    /// Called before another repository is opened or the window is closed.
+    pub fn flush_deletes(&mut self) -> Option<Command> {
+        let ids: Vec<Id> = std::mem::take(&mut self.pending_deletes)
+            .into_values()
+            .flatten()
+            .collect();
+        let repository = self.repository.as_ref()?;
+        (!ids.is_empty()).then(|| {
+            let path = repository.path.display().to_string();
+            Command::DeleteSnapshots(path, self.password.clone(), ids)
+        })
+    }
+
+    /// Filters the snapshots, sorts them by the selected column and splits them into the
+    /// selected groups.
+    fn groups<'a>(
+        &self,
+        snapshots: &'a [SnapshotFile],
+        filter: &SnapshotFilter,
+    ) -> Vec<(String, Vec<&'a SnapshotFile>)> {
+        let mut sorted: Vec<&SnapshotFile> = snapshots
+            .iter()
+            .filter(|s| {
+                !self
+                    .pending_deletes
+                    .values()
+                    .flatten()
+                    .any(|id| *id == s.id)
+            })
+            .filter(|s| filter.matches(s))
+            .collect();
+        sorted.sort_by(|a, b| {
+            let order = compare_snapshots(self.sort, a, b, self.cost_map());
+            if self.sort_ascending {
+                order
+            } else {
+                order.reverse()
+            }
+        });
+
+        let today = Local::now().date_naive();
+        let key = |snapshot: &SnapshotFile| match self.group_by {
+            GroupBy::None => String::new(),
+            GroupBy::Host => snapshot.hostname.clone(),
+            GroupBy::Paths => join(&snapshot.paths),
+            GroupBy::Date => match (today - snapshot.time.date_naive()).num_days() {
+                days if days <= 0 => String::from("0"),
+                days if days < 7 => String::from("1"),
+                _ => String::from("2"),
+            },
+        };
+
+        let mut groups: BTreeMap<String, Vec<&SnapshotFile>> = BTreeMap::new();
+        for snapshot in sorted {
+            groups.entry(key(snapshot)).or_default().push(snapshot);
+        }
+
+        groups
+            .into_iter()
+            .map(|(key, items)| {
+                let title = match (self.group_by, key.as_str()) {
+                    (GroupBy::None, _) => fl!("snapshots"),
+                    (GroupBy::Date, "0") => fl!("today"),
+                    (GroupBy::Date, "1") => fl!("this-week"),
+                    (GroupBy::Date, _) => fl!("older"),
+                    (_, "") => fl!("unknown"),
+                    (_, key) => key.to_string(),
+                };
+                (title, items)
+            })
+            .collect()
+    }
+
+    fn columns_header(&self) -> Element<Message> {
+        let spacing = theme::active().cosmic().spacing;
+
+        let button = |column: SortColumn, label: String, portion: u16| {
+            let label = match (self.sort == column, self.sort_ascending) {
+                (true, true) => format!("{} ▲", label),
+                (true, false) => format!("{} ▼", label),
+                (false, _) => label,
+            };
+            widget::button::text(label)
+                .on_press(Message::SortBy(column))
+                .width(Length::FillPortion(portion))
+        };
+
+        let visible = self.visible();
+        let all_selected =
+            !visible.is_empty() && visible.iter().all(|id| self.selected.contains(id));
+        let select_all = widget::checkbox("", all_selected).on_toggle(|checked| {
+            if checked {
+                Message::SelectAll
+            } else {
+                Message::ClearSelection
+            }
+        });
+
+        widget::row::with_capacity(9)
+            .align_items(Alignment::Center)
+            .spacing(spacing.space_xxs)
+            .padding([0, spacing.space_xxs])
+            .push(select_all)
+            .push(button(SortColumn::Time, fl!("time"), 3))
+            .push(button(SortColumn::Host, fl!("host"), 2))
+            .push(button(SortColumn::Paths, fl!("paths"), 3))
+            .push(button(SortColumn::Tags, fl!("tags"), 2))
+            .push(button(SortColumn::Size, fl!("size"), 2))
+            .push(button(SortColumn::Added, fl!("added-data"), 2))
+            .push(button(SortColumn::Unique, fl!("unique-data"), 2))
+            .push(widget::horizontal_space(Length::Fixed(ACTIONS_WIDTH)))
+            .into()
+    }
+
+    fn snapshot_row<'a>(&'a self, item: &'a SnapshotFile) -> Element<'a, Message> {
+        let spacing = theme::active().cosmic().spacing;
+
+        let delete_button = widget::button(IconCache::get("user-trash-full-symbolic", 18))
+            .padding(spacing.space_xxs)
+            .style(theme::Button::Destructive)
+            .on_press(Message::Delete(item.id));
+
+        let compare_button = if self.compare == Some(item.id) {
+            widget::button::suggested(fl!("comparing"))
+        } else {
+            widget::button::standard(fl!("compare"))
+        }
+        .on_press(Message::Compare(item.id));
+
+        let compare_live_button =
+            widget::button::standard(fl!("compare-live")).on_press(Message::CompareLive(item.id));
+
+        let export_button =
+            widget::button::standard(fl!("export")).on_press(Message::Export(item.id));
+
+        let details_button = widget::button(IconCache::get("info-outline-symbolic", 18))
+            .padding(spacing.space_xxs)
+            .style(theme::Button::Standard)
+            .on_press(Message::Select(item.id));
+
+        let cell = |text: String, portion: u16| {
+            widget::text::body(text).width(Length::FillPortion(portion))
+        };
+
+        let time = widget::column::with_capacity(2)
+            .push(widget::text::body(format_relative(&item.time)))
+            .push(widget::text::caption(format_time(&item.time)))
+            .width(Length::FillPortion(3));
+
+        let (size, added) = match &item.summary {
+            Some(summary) => (
+                ByteSize::b(summary.total_bytes_processed).to_string(),
+                ByteSize::b(summary.data_added).to_string(),
+            ),
+            None => (String::new(), String::new()),
+        };
+
+        let id = item.id;
+        let checkbox = widget::checkbox("", self.selected.contains(&id))
+            .on_toggle(move |_| Message::ToggleSelected(id));
+
+        widget::row::with_capacity(13)
+            .align_items(Alignment::Center)
+            .spacing(spacing.space_xxs)
+            .padding([spacing.space_xxxs, spacing.space_xxs])
+            .push(checkbox)
+            .push(time)
+            .push(cell(item.hostname.clone(), 2))
+            .push(cell(join(&item.paths), 3))
+            .push(cell(join(&item.tags), 2))
+            .push(cell(size, 2))
+            .push(cell(added, 2))
+            .push(cell(self.unique_label(item.id), 2))
+            .push(details_button)
+            .push(compare_button)
+            .push(compare_live_button)
+            .push(export_button)
+            .push(delete_button)
+            .into()
+    }
+
     pub fn empty<'a>(&'a self, repository: &'a Repository) -> Element<'a, Message> {
         let spacing = theme::active().cosmic().spacing;
 
@@ -177,13 +1285,1008 @@ impl Content {
             .into()
     }
 
-    fn repository_header<'a>(&'a self, repository: &'a Repository) -> Element<'a, Message> {
+    fn diff_view<'a>(
+        &'a self,
+        title: &'a str,
+        live: bool,
+        changes: Option<&'a Result<Vec<Change>, String>>,
+    ) -> Element<'a, Message> {
+        let spacing = theme::active().cosmic().spacing;
+
+        let header = widget::row::with_capacity(2)
+            .align_items(Alignment::Center)
+            .spacing(spacing.space_s)
+            .push(
+                widget::button::icon(widget::icon::from_name("go-previous-symbolic"))
+                    .on_press(Message::Back),
+            )
+            .push(widget::text::title3(title).width(Length::Fill));
+
+        let body: Element<'a, Message> = match changes {
+            None => widget::text(fl!("loading-changes")).into(),
+            Some(Err(error)) => widget::text(error).into(),
+            Some(Ok(changes)) if changes.is_empty() => widget::text(fl!("no-changes")).into(),
+            Some(Ok(changes)) => {
+                let count = |kind| changes.iter().filter(|c| c.kind == kind).count();
+                let delta: i64 = changes.iter().map(Change::size_delta).sum();
+                let summary = widget::text::body(fl!(
+                    "diff-summary",
+                    added = count(ChangeKind::Added),
+                    removed = count(ChangeKind::Removed),
+                    modified = count(ChangeKind::Modified) + count(ChangeKind::Metadata),
+                    delta = format_delta(delta)
+                ));
+
+                let mut section = widget::settings::view_section(fl!("changes"));
+                for change in changes.iter().take(MAX_ROWS) {
+                    let kind = match change.kind {
+                        ChangeKind::Added if live => fl!("created"),
+                        ChangeKind::Removed if live => fl!("deleted"),
+                        ChangeKind::Added => fl!("added"),
+                        ChangeKind::Removed => fl!("removed"),
+                        ChangeKind::Modified => fl!("modified"),
+                        ChangeKind::Metadata => fl!("metadata-changed"),
+                    };
+                    let detail = match change.kind {
+                        ChangeKind::Metadata => kind,
+                        _ => format!("{} ({})", kind, format_delta(change.size_delta())),
+                    };
+                    section = section.add(widget::settings::item(
+                        change.path.display().to_string(),
+                        widget::text::body(detail),
+                    ));
+                }
+
+                let more = (changes.len() > MAX_ROWS).then(|| {
+                    widget::text::body(fl!("more-changes", count = changes.len() - MAX_ROWS))
+                });
+
+                let restore_hint = live.then(|| widget::text::body(fl!("diff-live-description")));
+
+                widget::column::with_capacity(4)
+                    .spacing(spacing.space_xxs)
+                    .push(summary)
+                    .push_maybe(restore_hint)
+                    .push(section)
+                    .push_maybe(more)
+                    .into()
+            }
+        };
+
+        widget::column::with_capacity(2)
+            .spacing(spacing.space_xxs)
+            .padding(spacing.space_xxs)
+            .push(header)
+            .push(body)
+            .apply(widget::container)
+            .height(Length::Shrink)
+            .apply(widget::scrollable)
+            .height(Length::Fill)
+            .into()
+    }
+
+    fn search_view<'a>(
+        &'a self,
+        query: &'a str,
+        matches: Option<&'a Result<Vec<Match>, String>>,
+    ) -> Element<'a, Message> {
+        let spacing = theme::active().cosmic().spacing;
+
+        let header = widget::row::with_capacity(2)
+            .align_items(Alignment::Center)
+            .spacing(spacing.space_s)
+            .push(
+                widget::button::icon(widget::icon::from_name("go-previous-symbolic"))
+                    .on_press(Message::Back),
+            )
+            .push(widget::text::title3(fl!("search-title", query = query)).width(Length::Fill));
+
+        let body: Element<'a, Message> = match matches {
+            None => widget::text(fl!("searching")).into(),
+            Some(Err(error)) => widget::text(error).into(),
+            Some(Ok(matches)) if matches.is_empty() => widget::text(fl!("no-results")).into(),
+            Some(Ok(matches)) => {
+                let mut section = widget::settings::view_section(fl!("results"));
+                for item in matches.iter().take(MAX_ROWS) {
+                    let details = fl!(
+                        "match-details",
+                        time = format_time(&item.time),
+                        size = ByteSize::b(item.size).to_string(),
+                        mtime = item.mtime.as_ref().map(format_time).unwrap_or_default()
+                    );
+                    let name = file_name(&item.path);
+                    let preview_button = widget::button::standard(fl!("preview"))
+                        .on_press(Message::Preview(item.snapshot_path(), name));
+                    let history_button = widget::button::standard(fl!("history"))
+                        .on_press(Message::History(item.path.clone()));
+                    let restore_button = widget::button::standard(fl!("restore"))
+                        .on_press(Message::Restore(item.snapshot_path()));
+                    section = section.add(widget::settings::item_row(vec![
+                        widget::column::with_capacity(2)
+                            .push(widget::text::body(item.path.display().to_string()))
+                            .push(widget::text::caption(details))
+                            .width(Length::Fill)
+                            .into(),
+                        preview_button.into(),
+                        history_button.into(),
+                        restore_button.into(),
+                    ]));
+                }
+
+                let more = (matches.len() > MAX_ROWS).then(|| {
+                    widget::text::body(fl!("more-results", count = matches.len() - MAX_ROWS))
+                });
+
+                widget::column::with_capacity(2)
+                    .spacing(spacing.space_xxs)
+                    .push(section)
+                    .push_maybe(more)
+                    .into()
+            }
+        };
+
+        widget::column::with_capacity(2)
+            .spacing(spacing.space_xxs)
+            .padding(spacing.space_xxs)
+            .push(header)
+            .push(body)
+            .apply(widget::container)
+            .height(Length::Shrink)
+            .apply(widget::scrollable)
+            .height(Length::Fill)
+            .into()
+    }
+
+    fn history_view<'a>(
+        &'a self,
+        path: &'a Path,
+        versions: Option<&'a Result<Vec<Version>, String>>,
+    ) -> Element<'a, Message> {
+        let spacing = theme::active().cosmic().spacing;
+
+        let header = widget::row::with_capacity(2)
+            .align_items(Alignment::Center)
+            .spacing(spacing.space_s)
+            .push(
+                widget::button::icon(widget::icon::from_name("go-previous-symbolic"))
+                    .on_press(Message::Back),
+            )
+            .push(
+                widget::text::title3(fl!("history-title", path = path.display().to_string()))
+                    .width(Length::Fill),
+            );
+
+        let body: Element<'a, Message> = match versions {
+            None => widget::text(fl!("loading-versions")).into(),
+            Some(Err(error)) => widget::text(error).into(),
+            Some(Ok(versions)) if versions.is_empty() => widget::text(fl!("no-versions")).into(),
+            Some(Ok(versions)) => {
+                let mut section = widget::settings::view_section(fl!("versions"));
+                for version in versions {
+                    let range = version
+                        .ranges
+                        .iter()
+                        .map(|(first, last)| {
+                            fl!(
+                                "version-range",
+                                first = format_time(first),
+                                last = format_time(last)
+                            )
+                        })
+                        .collect::<Vec<_>>()
+                        .join(", ");
+                    let details = fl!(
+                        "version-details",
+                        size = ByteSize::b(version.size).to_string(),
+                        mtime = version.mtime.as_ref().map(format_time).unwrap_or_default(),
+                        count = version.snapshot_count
+                    );
+                    let preview_button = widget::button::standard(fl!("preview"))
+                        .on_press(Message::Preview(version.snapshot_path(), file_name(path)));
+                    let restore_button = widget::button::standard(fl!("restore"))
+                        .on_press(Message::Restore(version.snapshot_path()));
+                    section = section.add(widget::settings::item_row(vec![
+                        widget::column::with_capacity(2)
+                            .push(widget::text::body(range))
+                            .push(widget::text::caption(details))
+                            .width(Length::Fill)
+                            .into(),
+                        preview_button.into(),
+                        restore_button.into(),
+                    ]));
+                }
+                section.into()
+            }
+        };
+
+        widget::column::with_capacity(2)
+            .spacing(spacing.space_xxs)
+            .padding(spacing.space_xxs)
+            .push(header)
+            .push(body)
+            .apply(widget::container)
+            .height(Length::Shrink)
+            .apply(widget::scrollable)
+            .height(Length::Fill)
+            .into()
+    }
+
+    fn statistics_view<'a>(
+        &'a self,
+        repository: &'a Repository,
+        statistics: Option<&'a Result<Statistics, String>>,
+    ) -> Element<'a, Message> {
+        let spacing = theme::active().cosmic().spacing;
+
+        let header = widget::row::with_capacity(2)
+            .align_items(Alignment::Center)
+            .spacing(spacing.space_s)
+            .push(
+                widget::button::icon(widget::icon::from_name("go-previous-symbolic"))
+                    .on_press(Message::Back),
+            )
+            .push(
+                widget::text::title3(fl!("statistics-title", name = repository.name.as_str()))
+                    .width(Length::Fill),
+            );
+
+        let body: Element<'a, Message> = match statistics {
+            None => widget::text(fl!("loading-statistics")).into(),
+            Some(Err(error)) => widget::text(error).into(),
+            Some(Ok(statistics)) => {
+                let size = |size: u64| ByteSize::b(size).to_string();
+                let item = |label: String, value: String| {
+                    widget::settings::item(label, widget::text::body(value))
+                };
+
+                let overview = widget::settings::view_section(fl!("overview"))
+                    .add(item(fl!("snapshots"), statistics.snapshots.to_string()))
+                    .add(item(fl!("total-size"), size(statistics.total_size)))
+                    .add(item(fl!("unique-size"), size(statistics.unique_size)))
+                    .add(item(
+                        fl!("compressed-size"),
+                        size(statistics.compressed_size),
+                    ))
+                    .add(item(
+                        fl!("compression-ratio"),
+                        format!("{:.2}×", statistics.compression_ratio()),
+                    ))
+                    .add(item(
+                        fl!("dedup-ratio"),
+                        format!("{:.2}×", statistics.dedup_ratio()),
+                    ))
+                    .add(item(fl!("packs"), statistics.packs.to_string()))
+                    .add(item(fl!("blobs"), statistics.blobs.to_string()))
+                    .add(item(
+                        fl!("pending-delete-size"),
+                        size(statistics.pending_delete_size),
+                    ));
+
+                let growth =
+                    &statistics.growth[statistics.growth.len().saturating_sub(GROWTH_DAYS)..];
+                let max = growth
+                    .iter()
+                    .map(|(_, size)| *size)
+                    .max()
+                    .unwrap_or_default();
+                let mut chart = widget::settings::view_section(fl!("growth"));
+                for (date, total) in growth {
+                    chart = chart.add(self.growth_row(date, *total, max));
+                }
+
+                widget::column::with_capacity(2)
+                    .spacing(spacing.space_m)
+                    .push(overview)
+                    .push(chart)
+                    .into()
+            }
+        };
+
+        widget::column::with_capacity(2)
+            .spacing(spacing.space_xxs)
+            .padding(spacing.space_xxs)
+            .push(header)
+            .push(body)
+            .apply(widget::container)
+            .height(Length::Shrink)
+            .apply(widget::scrollable)
+            .height(Length::Fill)
+            .into()
+    }
+
+    fn profiles_view<'a>(
+        &'a self,
+        repository: &'a Repository,
+        form: &'a ProfileForm,
+    ) -> Element<'a, Message> {
+        let spacing = theme::active().cosmic().spacing;
+
+        let header = widget::row::with_capacity(2)
+            .align_items(Alignment::Center)
+            .spacing(spacing.space_s)
+            .push(
+                widget::button::icon(widget::icon::from_name("go-previous-symbolic"))
+                    .on_press(Message::Back),
+            )
+            .push(
+                widget::text::title3(fl!("profiles-title", name = repository.name.as_str()))
+                    .width(Length::Fill),
+            );
+
+        let mut profiles = widget::settings::view_section(fl!("profiles"));
+        if repository.profiles.is_empty() {
+            profiles = profiles.add(widget::text::body(fl!("no-profiles")));
+        }
+        for (index, profile) in repository.profiles.iter().enumerate() {
+            let description = match &profile.source {
+                Source::Paths(paths) => paths
+                    .iter()
+                    .map(|path| path.display().to_string())
+                    .collect::<Vec<_>>()
+                    .join(", "),
+                Source::Command { command, filename } => fl!(
+                    "command-source",
+                    command = command.as_str(),
+                    filename = filename.as_str()
+                ),
+            };
+            let run = match &self.running {
+                Some(name) if *name == profile.name => widget::button::standard(fl!("running")),
+                Some(_) => widget::button::standard(fl!("run")),
+                None => widget::button::standard(fl!("run")).on_press(Message::RunProfile(index)),
+            };
+            let description = match profile.hooks.is_empty() {
+                true => description,
+                false => fl!("with-hooks", description = description),
+            };
+            let description = match &profile.parent {
+                Parent::Latest => description,
+                Parent::Snapshot(id) => fl!(
+                    "with-parent",
+                    description = description,
+                    parent = id.as_str()
+                ),
+                Parent::Rescan => fl!("with-rescan", description = description),
+            };
+            profiles = profiles.add(widget::settings::item_row(vec![
+                widget::column::with_capacity(2)
+                    .push(widget::text::body(&profile.name))
+                    .push(widget::text::caption(description))
+                    .width(Length::Fill)
+                    .into(),
+                run.into(),
+                widget::button::icon(widget::icon::from_name("edit-delete-symbolic"))
+                    .on_press(Message::RemoveProfile(index))
+                    .into(),
+            ]));
+        }
+
+        let input = |placeholder: String, value: &'a str, field: ProfileField| {
+            widget::text_input(placeholder, value)
+                .on_input(move |value| Message::ProfileInput(field, value))
+        };
+        let mut new_profile = widget::settings::view_section(fl!("new-profile"))
+            .add(widget::settings::item(
+                fl!("name"),
+                input(fl!("profile-name"), &form.name, ProfileField::Name),
+            ))
+            .add(widget::settings::item(
+                fl!("source"),
+                widget::dropdown(
+                    &self.source_options,
+                    Some(usize::from(form.command)),
+                    Message::ProfileSource,
+                ),
+            ));
+        new_profile = match form.command {
+            false => new_profile.add(widget::settings::item(
+                fl!("paths"),
+                input(fl!("profile-paths"), &form.paths, ProfileField::Paths),
+            )),
+            true => new_profile
+                .add(widget::settings::item(
+                    fl!("command"),
+                    input(
+                        fl!("profile-command"),
+                        &form.command_line,
+                        ProfileField::Command,
+                    ),
+                ))
+                .add(widget::settings::item(
+                    fl!("filename"),
+                    input(
+                        fl!("profile-filename"),
+                        &form.filename,
+                        ProfileField::Filename,
+                    ),
+                )),
+        };
+        new_profile = new_profile.add(
+            widget::settings::item::builder(fl!("parent"))
+                .description(fl!("parent-description"))
+                .control(widget::dropdown(
+                    &self.parent_options,
+                    Some(form.parent),
+                    Message::ProfileParent,
+                )),
+        );
+        if form.parent == 1 {
+            new_profile = new_profile.add(widget::settings::item(
+                fl!("parent-id"),
+                input(
+                    fl!("parent-id-placeholder"),
+                    &form.parent_snapshot,
+                    ProfileField::ParentSnapshot,
+                ),
+            ));
+        }
+        // a rescan reads every file anyway
+        if form.parent != 2 {
+            new_profile = new_profile
+                .add(
+                    widget::settings::item::builder(fl!("ignore-ctime"))
+                        .description(fl!("ignore-ctime-description"))
+                        .toggler(form.detection.ignore_ctime, Message::ProfileIgnoreCtime),
+                )
+                .add(
+                    widget::settings::item::builder(fl!("ignore-inode"))
+                        .description(fl!("ignore-inode-description"))
+                        .toggler(form.detection.ignore_inode, Message::ProfileIgnoreInode),
+                );
+        }
+        new_profile = new_profile
+            .add(widget::settings::item(
+                fl!("pre-hook"),
+                input(
+                    fl!("pre-hook-placeholder"),
+                    &form.pre_hook,
+                    ProfileField::PreHook,
+                ),
+            ))
+            .add(widget::settings::item(
+                fl!("post-hook"),
+                input(
+                    fl!("post-hook-placeholder"),
+                    &form.post_hook,
+                    ProfileField::PostHook,
+                ),
+            ))
+            .add(widget::settings::item(
+                fl!("failure-hook"),
+                input(
+                    fl!("failure-hook-placeholder"),
+                    &form.failure_hook,
+                    ProfileField::FailureHook,
+                ),
+            ))
+            .add(widget::settings::item(
+                fl!("hook-timeout"),
+                input(
+                    fl!("hook-timeout-placeholder", seconds = DEFAULT_TIMEOUT),
+                    &form.timeout,
+                    ProfileField::Timeout,
+                ),
+            ));
+        new_profile = new_profile.add(
+            widget::row::with_capacity(2)
+                .push(widget::horizontal_space(Length::Fill))
+                .push(
+                    widget::button::suggested(fl!("add-profile"))
+                        .on_press_maybe(form.profile().map(|_| Message::AddProfile)),
+                ),
+        );
+
+        widget::column::with_capacity(3)
+            .spacing(spacing.space_m)
+            .padding(spacing.space_xxs)
+            .push(header)
+            .push(profiles)
+            .push(new_profile)
+            .apply(widget::container)
+            .height(Length::Shrink)
+            .apply(widget::scrollable)
+            .height(Length::Fill)
+            .into()
+    }
+
+    fn runs_view<'a>(
+        &'a self,
+        repository: &'a Repository,
+        runs: Option<&'a Result<Vec<Run>, String>>,
+        expanded: Option<usize>,
+    ) -> Element<'a, Message> {
+        let spacing = theme::active().cosmic().spacing;
+
+        let maintain = |label: String, operation: Operation| match self.maintenance {
+            Some(current) if current == operation => widget::button::standard(fl!("running")),
+            Some(_) => widget::button::standard(label),
+            None => widget::button::standard(label).on_press(Message::Maintain(operation)),
+        };
+        let exportable = runs.is_some_and(|runs| runs.as_ref().is_ok_and(|runs| !runs.is_empty()));
+        let header = widget::row::with_capacity(5)
+            .align_items(Alignment::Center)
+            .spacing(spacing.space_s)
+            .push(
+                widget::button::icon(widget::icon::from_name("go-previous-symbolic"))
+                    .on_press(Message::Back),
+            )
+            .push(
+                widget::text::title3(fl!("runs-title", name = repository.name.as_str()))
+                    .width(Length::Fill),
+            )
+            .push(maintain(fl!("check"), Operation::Check))
+            .push(maintain(fl!("prune"), Operation::Prune))
+            .push(
+                widget::button::standard(fl!("export"))
+                    .on_press_maybe(exportable.then_some(Message::ExportRuns)),
+            );
+
+        let body: Element<'a, Message> = match runs {
+            None => widget::text(fl!("loading-runs")).into(),
+            Some(Err(error)) => widget::text(error).into(),
+            Some(Ok(runs)) if runs.is_empty() => widget::text(fl!("no-runs")).into(),
+            Some(Ok(runs)) => {
+                let mut section = widget::settings::view_section(fl!("runs"));
+                for (index, run) in runs.iter().enumerate() {
+                    section = section.add(self.run_row(index, run, expanded == Some(index)));
+                }
+                section.into()
+            }
+        };
+
+        widget::column::with_capacity(2)
+            .spacing(spacing.space_xxs)
+            .padding(spacing.space_xxs)
+            .push(header)
+            .push(body)
+            .apply(widget::container)
+            .height(Length::Shrink)
+            .apply(widget::scrollable)
+            .height(Length::Fill)
+            .into()
+    }
+
+    fn run_row<'a>(&'a self, index: usize, run: &'a Run, expanded: bool) -> Element<'a, Message> {
+        let operation = match (run.operation, &run.profile) {
+            (Operation::Backup, Some(profile)) => fl!("backup-of", profile = profile.as_str()),
+            (Operation::Backup, None) => fl!("backup"),
+            (Operation::Prune, _) => fl!("prune"),
+            (Operation::Check, _) => fl!("check"),
+        };
+        let duration = (run.finished - run.started).num_seconds();
+        let mut details = vec![
+            run.started.format("%Y-%m-%d %H:%M:%S").to_string(),
+            fl!("run-duration", seconds = duration),
+        ];
+        if let Some(snapshot) = &run.snapshot {
+            details.push(snapshot.chars().take(8).collect());
+        }
+        if let Some(summary) = &run.summary {
+            details.push(reuse(summary));
+            details.push(fl!(
+                "run-summary",
+                new = summary.files_new,
+                changed = summary.files_changed,
+                unmodified = summary.files_unmodified,
+                added = ByteSize::b(summary.data_added).to_string()
+            ));
+        }
+        let result = match &run.error {
+            None => fl!("run-succeeded"),
+            Some(error) => fl!("run-failed", error = error.as_str()),
+        };
+
+        let mut column = widget::column::with_capacity(4)
+            .push(widget::text::body(operation))
+            .push(widget::text::caption(details.join(" · ")))
+            .push(widget::text::caption(result))
+            .width(Length::Fill);
+        if expanded {
+            column = column.push(widget::text::monotext(run.log.as_str()));
+        }
+
+        let toggle = (!run.log.is_empty()).then(|| {
+            let label = match expanded {
+                true => fl!("hide-log"),
+                false => fl!("show-log"),
+            };
+            widget::button::text(label).on_press(Message::ToggleRunLog(index))
+        });
+
+        widget::row::with_capacity(2)
+            .align_items(Alignment::Center)
+            .push(column)
+            .push_maybe(toggle)
+            .into()
+    }
+
+    fn cost_map(&self) -> Option<&HashMap<Id, SnapshotCost>> {
+        self.costs.as_ref().and_then(|costs| costs.as_ref().ok())
+    }
+
+    /// The data only the snapshot references, or a placeholder while it is computed.
+    fn unique_label(&self, id: Id) -> String {
+        match &self.costs {
+            None => fl!("computing"),
+            Some(Err(_)) => String::new(),
+            Some(Ok(costs)) => costs
+                .get(&id)
+                .map(|cost| ByteSize::b(cost.unique).to_string())
+                .unwrap_or_default(),
+        }
+    }
+
+    fn details_view(&self, id: Id) -> Element<Message> {
+        let spacing = theme::active().cosmic().spacing;
+
+        let header = widget::row::with_capacity(2)
+            .align_items(Alignment::Center)
+            .spacing(spacing.space_s)
+            .push(
+                widget::button::icon(widget::icon::from_name("go-previous-symbolic"))
+                    .on_press(Message::Back),
+            )
+            .push(
+                widget::text::title3(fl!("details-title", id = id.to_string())).width(Length::Fill),
+            );
+
+        let Some(snapshot) = self
+            .snapshots
+            .iter()
+            .flatten()
+            .find(|snapshot| snapshot.id == id)
+        else {
+            return widget::column::with_capacity(2)
+                .spacing(spacing.space_xxs)
+                .padding(spacing.space_xxs)
+                .push(header)
+                .push(widget::text(fl!("snapshot-not-found")))
+                .into();
+        };
+
+        let size = |size: u64| ByteSize::b(size).to_string();
+        let item =
+            |label: String, value: String| widget::settings::item(label, widget::text::body(value));
+
+        let mut details = widget::settings::view_section(fl!("snapshot"))
+            .add(item(fl!("id"), snapshot.id.to_hex().as_str().to_string()))
+            .add(item(fl!("time"), format_time(&snapshot.time)))
+            .add(item(fl!("host"), snapshot.hostname.clone()))
+            .add(item(fl!("paths"), join(&snapshot.paths)))
+            .add(item(fl!("tags"), join(&snapshot.tags)));
+        if !snapshot.label.is_empty() {
+            details = details.add(item(fl!("label"), snapshot.label.clone()));
+        }
+
+        let backup = snapshot.summary.as_ref().map(|summary| {
+            widget::settings::view_section(fl!("backup"))
+                .add(item(fl!("size"), size(summary.total_bytes_processed)))
+                .add(item(
+                    fl!("files"),
+                    fl!(
+                        "files-summary",
+                        new = summary.files_new,
+                        changed = summary.files_changed,
+                        unmodified = summary.files_unmodified
+                    ),
+                ))
+                .add(item(fl!("added-data"), size(summary.data_added)))
+                .add(item(fl!("added-packed"), size(summary.data_added_packed)))
+                .add(item(
+                    fl!("duration"),
+                    format!("{:.1} s", summary.backup_duration),
+                ))
+        });
+
+        let cost: Element<Message> = match &self.costs {
+            None => widget::text(fl!("computing-cost")).into(),
+            Some(Err(error)) => widget::text(error).into(),
+            Some(Ok(costs)) => {
+                let cost = costs.get(&id).copied().unwrap_or_default();
+                widget::settings::view_section(fl!("storage-cost"))
+                    .add(item(fl!("unique-cost"), size(cost.unique)))
+                    .add(item(fl!("shared-cost"), size(cost.shared)))
+                    .into()
+            }
+        };
+
+        let header = header.push(
+            widget::button::standard(fl!("disk-usage")).on_press(Message::Usage(snapshot.id)),
+        );
+
+        widget::column::with_capacity(4)
+            .spacing(spacing.space_m)
+            .padding(spacing.space_xxs)
+            .push(header)
+            .push(details)
+            .push_maybe(backup)
+            .push(cost)
+            .apply(widget::container)
+            .height(Length::Shrink)
+            .apply(widget::scrollable)
+            .height(Length::Fill)
+            .into()
+    }
+
+    fn usage_view<'a>(
+        &'a self,
+        snapshot: Id,
+        usage: Option<&'a Result<DirUsage, String>>,
+        path: &[usize],
+    ) -> Element<'a, Message> {
+        let spacing = theme::active().cosmic().spacing;
+
+        let header = widget::row::with_capacity(2)
+            .align_items(Alignment::Center)
+            .spacing(spacing.space_s)
+            .push(
+                widget::button::icon(widget::icon::from_name("go-previous-symbolic"))
+                    .on_press(Message::Back),
+            )
+            .push(
+                widget::text::title3(fl!("usage-title", id = snapshot.to_string()))
+                    .width(Length::Fill),
+            );
+
+        let body: Element<'a, Message> = match usage {
+            None => widget::text(fl!("loading-usage")).into(),
+            Some(Err(error)) => widget::text(error).into(),
+            Some(Ok(root)) => {
+                let dir = path
+                    .iter()
+                    .try_fold(root, |dir, index| dir.dirs.get(*index))
+                    .unwrap_or(root);
+
+                let location = widget::row::with_capacity(3)
+                    .align_items(Alignment::Center)
+                    .spacing(spacing.space_xxs)
+                    .push_maybe((!path.is_empty()).then(|| {
+                        widget::button::icon(widget::icon::from_name("go-up-symbolic"))
+                            .on_press(Message::ParentDir)
+                    }))
+                    .push(
+                        widget::text::heading(dir.absolute_path().display().to_string())
+                            .width(Length::Fill),
+                    )
+                    .push(widget::text::body(fl!(
+                        "usage-total",
+                        size = ByteSize::b(dir.size).to_string(),
+                        files = dir.files
+                    )));
+
+                let sort_button = |sort: UsageSort, label: String| {
+                    let label = if self.usage_sort == sort {
+                        format!("{} ▼", label)
+                    } else {
+                        label
+                    };
+                    widget::button::text(label).on_press(Message::UsageSortBy(sort))
+                };
+                let sorting = widget::row::with_capacity(4)
+                    .align_items(Alignment::Center)
+                    .spacing(spacing.space_xxs)
+                    .push(widget::text::body(fl!("sort-by")))
+                    .push(sort_button(UsageSort::Size, fl!("size")))
+                    .push(sort_button(UsageSort::Files, fl!("files")))
+                    .push(sort_button(UsageSort::Name, fl!("name")));
+
+                let mut dirs: Vec<(usize, &DirUsage)> = dir.dirs.iter().enumerate().collect();
+                match self.usage_sort {
+                    UsageSort::Size => {}
+                    UsageSort::Files => dirs.sort_by(|(_, a), (_, b)| b.files.cmp(&a.files)),
+                    UsageSort::Name => dirs.sort_by_key(|(_, dir)| dir.name().to_lowercase()),
+                }
+
+                let mut section = widget::settings::view_section(fl!("folders"));
+                for (index, child) in dirs.into_iter().take(MAX_ROWS) {
+                    section = section.add(self.usage_row(dir.size, Some(index), child));
+                }
+                let direct_size = dir.size - dir.dirs.iter().map(|child| child.size).sum::<u64>();
+                if direct_size > 0 {
+                    let direct = DirUsage {
+                        path: dir.path.join(fl!("files-in-folder")),
+                        size: direct_size,
+                        ..Default::default()
+                    };
+                    section = section.add(self.usage_row(dir.size, None, &direct));
+                }
+
+                widget::column::with_capacity(3)
+                    .spacing(spacing.space_xxs)
+                    .push(location)
+                    .push(sorting)
+                    .push(section)
+                    .into()
+            }
+        };
+
+        widget::column::with_capacity(2)
+            .spacing(spacing.space_xxs)
+            .padding(spacing.space_xxs)
+            .push(header)
+            .push(body)
+            .apply(widget::container)
+            .height(Length::Shrink)
+            .apply(widget::scrollable)
+            .height(Length::Fill)
+            .into()
+    }
+
+    /// A directory with a bar showing its share of `total`, `index` is set for directories that
+    /// can be opened.
+    fn usage_row<'a>(
+        &'a self,
+        total: u64,
+        index: Option<usize>,
+        dir: &DirUsage,
+    ) -> Element<'a, Message> {
+        let spacing = theme::active().cosmic().spacing;
+        let share = match total {
+            0 => 0.0,
+            total => dir.size as f32 / total as f32,
+        };
+
+        let name: Element<'a, Message> = match index {
+            Some(index) if !dir.dirs.is_empty() => widget::button::text(dir.name())
+                .on_press(Message::OpenDir(index))
+                .into(),
+            _ => widget::text::body(dir.name()).into(),
+        };
+
+        widget::row::with_capacity(6)
+            .align_items(Alignment::Center)
+            .spacing(spacing.space_s)
+            .push(widget::container(name).width(Length::FillPortion(3)))
+            .push(
+                cosmic::iced::widget::progress_bar(0.0..=1.0, share)
+                    .height(Length::Fixed(8.0))
+                    .width(Length::FillPortion(3)),
+            )
+            .push(
+                widget::text::body(format!("{:.1} %", share * 100.0))
+                    .width(Length::Fixed(64.0))
+                    .horizontal_alignment(Horizontal::Right),
+            )
+            .push(
+                widget::text::body(ByteSize::b(dir.size).to_string())
+                    .width(Length::Fixed(96.0))
+                    .horizontal_alignment(Horizontal::Right),
+            )
+            .push_maybe(index.map(|_| {
+                widget::button::standard(fl!("exclude"))
+                    .on_press(Message::Exclude(dir.absolute_path()))
+            }))
+            .into()
+    }
+
+    /// A bar of the growth chart, the repository size at the end of `date` relative to `max`.
+    fn growth_row(&self, date: &NaiveDate, total: u64, max: u64) -> Element<Message> {
         let spacing = theme::active().cosmic().spacing;
 
         widget::row::with_capacity(3)
+            .align_items(Alignment::Center)
+            .spacing(spacing.space_s)
+            .push(
+                widget::text::body(date.format("%Y-%m-%d").to_string()).width(Length::Fixed(96.0)),
+            )
+            .push(
+                cosmic::iced::widget::progress_bar(0.0..=max.max(1) as f32, total as f32)
+                    .height(Length::Fixed(8.0)),
+            )
+            .push(
+                widget::text::body(ByteSize::b(total).to_string())
+                    .width(Length::Fixed(96.0))
+                    .horizontal_alignment(Horizontal::Right),
+            )
+            .into()
+    }
+
+    /// The file preview shown in the context drawer.
+    pub fn preview_view(&self) -> Element<Message> {
+        let spacing = theme::active().cosmic().spacing;
+
+        let Some(preview) = &self.preview else {
+            return widget::text(fl!("no-preview")).into();
+        };
+
+        let content: Element<Message> = match (&preview.content, &preview.image) {
+            (None, _) => widget::text(fl!("loading-preview")).into(),
+            (Some(Err(error)), _) => widget::text(error).into(),
+            (Some(Ok(Preview::Image(_))), Some(image)) => {
+                widget::image(image.clone()).width(Length::Fill).into()
+            }
+            (Some(Ok(Preview::Text { content, truncated })), _) => widget::column::with_capacity(2)
+                .spacing(spacing.space_xxs)
+                .push(widget::text::monotext(content.as_str()))
+                .push_maybe(truncated.then(|| {
+                    widget::text::caption(fl!(
+                        "preview-truncated",
+                        limit = ByteSize::b(TEXT_LIMIT).to_string()
+                    ))
+                }))
+                .into(),
+            (Some(Ok(Preview::TooLarge(size))), _) => widget::text(fl!(
+                "preview-too-large",
+                size = ByteSize::b(*size).to_string()
+            ))
+            .into(),
+            (Some(Ok(_)), _) => widget::text(fl!("preview-unsupported")).into(),
+        };
+
+        widget::column::with_capacity(3)
+            .spacing(spacing.space_s)
+            .push(widget::text::heading(preview.name.as_str()))
+            .push(
+                widget::button::standard(fl!("save-file")).on_press(Message::SaveFile(
+                    preview.snap_path.clone(),
+                    preview.name.clone(),
+                )),
+            )
+            .push(content)
+            .into()
+    }
+
+    fn repository_header<'a>(&'a self, repository: &'a Repository) -> Element<'a, Message> {
+        let spacing = theme::active().cosmic().spacing;
+
+        let mounted = self
+            .mount
+            .as_ref()
+            .filter(|(path, _)| path == &repository.path)
+            .map(|(_, mountpoint)| {
+                widget::row::with_capacity(2)
+                    .align_items(Alignment::Center)
+                    .spacing(spacing.space_xxs)
+                    .push(widget::text::caption(fl!(
+                        "mounted-at",
+                        path = mountpoint.display().to_string()
+                    )))
+                    .push(widget::button::standard(fl!("unmount")).on_press(Message::Unmount))
+            });
+
+        let mirror = repository
+            .mirror
+            .as_ref()
+            .zip(self.mirror_status.as_ref())
+            .map(|(mirror, (target, pending))| {
+                let synced = match mirror.last_synced {
+                    Some(time) => fl!(
+                        "mirror-synced",
+                        target = target.as_str(),
+                        time = time.format("%Y-%m-%d %H:%M").to_string()
+                    ),
+                    None => fl!("mirror-never-synced", target = target.as_str()),
+                };
+                let pending = match pending {
+                    Ok(count) => fl!("mirror-pending", count = count),
+                    Err(err) => fl!("mirror-unavailable", error = err.as_str()),
+                };
+                let sync = match self.syncing {
+                    true => widget::button::standard(fl!("syncing")),
+                    false => widget::button::standard(fl!("sync-now")).on_press(Message::Sync),
+                };
+                widget::row::with_capacity(2)
+                    .align_items(Alignment::Center)
+                    .spacing(spacing.space_xxs)
+                    .push(widget::text::caption(format!("{synced} · {pending}")))
+                    .push(sync)
+            });
+
+        widget::row::with_capacity(7)
             .align_items(Alignment::Center)
             .spacing(spacing.space_s)
             .push(widget::text::title3(&repository.name).width(Length::Fill))
+            .push_maybe(mirror)
+            .push_maybe(mounted)
+            .push(widget::button::standard(fl!("profiles")).on_press(Message::Profiles))
+            .push(widget::button::standard(fl!("run-history")).on_press(Message::Runs))
+            .push(widget::button::standard(fl!("statistics")).on_press(Message::Statistics))
+            .push(
+                widget::search_input(fl!("search-placeholder"), &self.query)
+                    .on_input(Message::SearchInput)
+                    .on_submit(Message::Search)
+                    .width(Length::Fixed(240.0)),
+            )
             .into()
     }
 
@@ -204,3 +2307,84 @@ impl Content {
         .into()
     }
 }
+
+fn compare_snapshots(
+    column: SortColumn,
+    a: &SnapshotFile,
+    b: &SnapshotFile,
+    costs: Option<&HashMap<Id, SnapshotCost>>,
+) -> Ordering {
+    let size = |snapshot: &SnapshotFile| {
+        snapshot
+            .summary
+            .as_ref()
+            .map(|summary| summary.total_bytes_processed)
+    };
+    let added =
+        |snapshot: &SnapshotFile| snapshot.summary.as_ref().map(|summary| summary.data_added);
+    let unique = |snapshot: &SnapshotFile| {
+        costs
+            .and_then(|costs| costs.get(&snapshot.id))
+            .map(|cost| cost.unique)
+    };
+
+    match column {
+        SortColumn::Time => a.time.cmp(&b.time),
+        SortColumn::Host => a.hostname.cmp(&b.hostname),
+        SortColumn::Paths => join(&a.paths).cmp(&join(&b.paths)),
+        SortColumn::Tags => join(&a.tags).cmp(&join(&b.tags)),
+        SortColumn::Size => size(a).cmp(&size(b)),
+        SortColumn::Added => added(a).cmp(&added(b)),
+        SortColumn::Unique => unique(a).cmp(&unique(b)),
+    }
+    .then_with(|| a.time.cmp(&b.time))
+}
+
+fn join(list: &StringList) -> String {
+    list.iter().cloned().collect::<Vec<_>>().join(", ")
+}
+
+fn format_relative(time: &DateTime<Local>) -> String {
+    let elapsed = Local::now().signed_duration_since(*time);
+    match (
+        elapsed.num_minutes(),
+        elapsed.num_hours(),
+        elapsed.num_days(),
+    ) {
+        (minutes, _, _) if minutes < 1 => fl!("just-now"),
+        (minutes, _, _) if minutes < 60 => fl!("minutes-ago", count = minutes),
+        (_, hours, _) if hours < 24 => fl!("hours-ago", count = hours),
+        (_, _, days) => fl!("days-ago", count = days),
+    }
+}
+
+fn file_name(path: &Path) -> String {
+    path.file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_default()
+}
+
+fn format_time(time: &DateTime<Local>) -> String {
+    time.format("%Y-%m-%d %H:%M:%S").to_string()
+}
+
+fn format_delta(delta: i64) -> String {
+    let size = ByteSize::b(delta.unsigned_abs());
+    if delta < 0 {
+        format!("-{}", size)
+    } else {
+        format!("+{}", size)
+    }
+}
+
+/// Which parent a backup compared files with and how many it could take over unread.
+pub fn reuse(summary: &RunSummary) -> String {
+    match &summary.parent {
+        Some(parent) => fl!(
+            "run-parent",
+            parent = parent.chars().take(8).collect::<String>(),
+            reused = summary.files_unmodified
+        ),
+        None => fl!("run-full-scan"),
+    }
+}
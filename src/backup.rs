@@ -1,6 +1,21 @@
+pub mod copy;
+pub mod cost;
+pub mod diff;
+pub mod export;
+pub mod filter;
+pub mod find;
+pub mod health;
+pub mod history;
+pub mod hooks;
 pub mod init;
+pub mod lock;
+pub mod maintenance;
+pub mod mount;
+pub mod preview;
 pub mod restore;
 pub mod snapshot;
+pub mod stats;
+pub mod usage;
 
 pub use init::init;
 pub use snapshot::snapshot;
@@ -0,0 +1,238 @@
+use rustic_backend::BackendOptions;
+use rustic_core::{
+    repofile::SnapshotFile, Id, NoProgressBars, OpenStatus, Repository, RepositoryOptions,
+};
+use std::collections::HashSet;
+use std::error::Error;
+use std::fmt;
+
+use crate::backup::lock::lock;
+
+/// Returns those of `snapshots` not yet present in the target repository, in the given order.
+///
+/// A snapshot is present if the target has a snapshot with the same tree, time, host, label,
+/// paths and tags, so copies made by an earlier run or by rustic itself are skipped.
+pub fn missing(
+    repository: &str,
+    password: &str,
+    target: &str,
+    target_password: &str,
+    snapshots: &[Id],
+) -> Result<Vec<Id>, Box<dyn Error>> {
+    let backends = BackendOptions::default()
+        .repository(repository)
+        .to_backends()?;
+    let repo_opts = RepositoryOptions::default().password(password);
+    let repo = Repository::new(&repo_opts, backends)?.open()?;
+
+    let target_backends = BackendOptions::default().repository(target).to_backends()?;
+    let target_opts = RepositoryOptions::default().password(target_password);
+    let target_repo = Repository::new(&target_opts, target_backends)?.open()?;
+
+    let missing: HashSet<Id> = missing_snapshots(&repo, &target_repo, |snapshot| {
+        snapshots.contains(&snapshot.id)
+    })?
+    .into_iter()
+    .map(|snapshot| snapshot.id)
+    .collect();
+
+    Ok(snapshots
+        .iter()
+        .filter(|id| missing.contains(id))
+        .copied()
+        .collect())
+}
+
+/// Counts the snapshots of the repository not yet copied to its mirror.
+pub fn pending(
+    repository: &str,
+    password: &str,
+    target: &str,
+    target_password: &str,
+) -> Result<usize, Box<dyn Error>> {
+    let backends = BackendOptions::default()
+        .repository(repository)
+        .to_backends()?;
+    let repo_opts = RepositoryOptions::default().password(password);
+    let repo = Repository::new(&repo_opts, backends)?.open()?;
+
+    let target_backends = BackendOptions::default().repository(target).to_backends()?;
+    let target_opts = RepositoryOptions::default().password(target_password);
+    let target_repo = Repository::new(&target_opts, target_backends)?.open()?;
+
+    Ok(missing_snapshots(&repo, &target_repo, |_| true)?.len())
+}
+
+/// Copies every snapshot the target lacks, returning how many were copied.
+#[tracing::instrument(skip_all, fields(repo = repository, operation = "sync"))]
+pub fn sync(
+    repository: &str,
+    password: &str,
+    target: &str,
+    target_password: &str,
+) -> Result<usize, Box<dyn Error>> {
+    let _lock = lock(repository, password, false)?;
+    let _target_lock = lock(target, target_password, false)?;
+
+    let backends = BackendOptions::default()
+        .repository(repository)
+        .to_backends()?;
+    let repo_opts = RepositoryOptions::default().password(password);
+    let repo = Repository::new(&repo_opts, backends)?.open()?;
+
+    let target_backends = BackendOptions::default().repository(target).to_backends()?;
+    let target_opts = RepositoryOptions::default().password(target_password);
+    let target_repo = Repository::new(&target_opts, target_backends)?.open()?;
+
+    let snapshots = missing_snapshots(&repo, &target_repo, |_| true)?;
+    let repo = repo.to_indexed()?;
+    let target_repo = target_repo.to_indexed_ids()?;
+    repo.copy(&target_repo, &snapshots)?;
+
+    tracing::info!(target, copied = snapshots.len(), "synced mirror");
+    Ok(snapshots.len())
+}
+
+/// Copies snapshots into a target repository one at a time, only uploading the blobs the target
+/// lacks.
+///
+/// Both repositories are opened and indexed once, and stay locked until the copier is dropped.
+pub struct Copier {
+    copy: Box<dyn Fn(Id) -> Result<(), Box<dyn Error>> + Send + Sync>,
+}
+
+impl fmt::Debug for Copier {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Copier").finish_non_exhaustive()
+    }
+}
+
+impl Copier {
+    #[tracing::instrument(skip_all, fields(repo = repository, operation = "copy"))]
+    pub fn open(
+        repository: &str,
+        password: &str,
+        target: &str,
+        target_password: &str,
+    ) -> Result<Self, Box<dyn Error>> {
+        let locks = (
+            lock(repository, password, false)?,
+            lock(target, target_password, false)?,
+        );
+
+        let backends = BackendOptions::default()
+            .repository(repository)
+            .to_backends()?;
+        let repo_opts = RepositoryOptions::default().password(password);
+        let repo = Repository::new(&repo_opts, backends)?
+            .open()?
+            .to_indexed()?;
+
+        let target_backends = BackendOptions::default().repository(target).to_backends()?;
+        let target_opts = RepositoryOptions::default().password(target_password);
+        let target_repo = Repository::new(&target_opts, target_backends)?
+            .open()?
+            .to_indexed_ids()?;
+
+        let copy = move |snapshot: Id| -> Result<(), Box<dyn Error>> {
+            let _locks = &locks;
+            let snapshot = repo.get_snapshot_from_str(snapshot.to_hex().as_str(), |_| true)?;
+            repo.copy(&target_repo, [&snapshot])?;
+            Ok(())
+        };
+        Ok(Self {
+            copy: Box::new(copy),
+        })
+    }
+
+    pub fn copy(&self, snapshot: Id) -> Result<(), Box<dyn Error>> {
+        (self.copy)(snapshot)
+    }
+}
+
+fn missing_snapshots(
+    repo: &Repository<NoProgressBars, OpenStatus>,
+    target_repo: &Repository<NoProgressBars, OpenStatus>,
+    filter: impl Fn(&SnapshotFile) -> bool,
+) -> Result<Vec<SnapshotFile>, Box<dyn Error>> {
+    let sources: Vec<SnapshotFile> = repo
+        .get_all_snapshots()?
+        .into_iter()
+        .filter(filter)
+        .collect();
+
+    Ok(target_repo
+        .relevant_copy_snapshots(|_| true, &sources)?
+        .into_iter()
+        .filter(|copy| copy.relevant)
+        .map(|copy| copy.sn)
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::backup::{init, snapshot, snapshot::fetch};
+
+    #[test]
+    fn test_copy() {
+        let dir = tempfile::tempdir().unwrap();
+        let (source, target) = (dir.path().join("source"), dir.path().join("target"));
+        let (source, target) = (source.to_str().unwrap(), target.to_str().unwrap());
+        let file = dir.path().join("file");
+        std::fs::write(&file, "content").unwrap();
+
+        assert!(init(source, "source").is_ok());
+        assert!(init(target, "target").is_ok());
+        assert!(snapshot(source, "source", vec![file.to_str().unwrap()]).is_ok());
+        let ids: Vec<Id> = fetch(source, "source")
+            .unwrap()
+            .iter()
+            .map(|snapshot| snapshot.id)
+            .collect();
+
+        assert_eq!(
+            missing(source, "source", target, "target", &ids).unwrap(),
+            ids
+        );
+        let copier = Copier::open(source, "source", target, "target").unwrap();
+        assert!(copier.copy(ids[0]).is_ok());
+        drop(copier);
+        assert!(missing(source, "source", target, "target", &ids)
+            .unwrap()
+            .is_empty());
+        assert_eq!(fetch(target, "target").unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_sync() {
+        let dir = tempfile::tempdir().unwrap();
+        let (source, target) = (dir.path().join("source"), dir.path().join("target"));
+        let (source, target) = (source.to_str().unwrap(), target.to_str().unwrap());
+        let (first, second) = (dir.path().join("first"), dir.path().join("second"));
+        std::fs::write(&first, "first").unwrap();
+        std::fs::write(&second, "second").unwrap();
+
+        assert!(init(source, "source").is_ok());
+        assert!(init(target, "target").is_ok());
+        assert!(snapshot(source, "source", vec![first.to_str().unwrap()]).is_ok());
+        assert!(snapshot(source, "source", vec![second.to_str().unwrap()]).is_ok());
+
+        // copies follow the order the snapshots were selected in
+        let ids: Vec<Id> = fetch(source, "source")
+            .unwrap()
+            .iter()
+            .rev()
+            .map(|snapshot| snapshot.id)
+            .collect();
+        assert_eq!(
+            missing(source, "source", target, "target", &ids).unwrap(),
+            ids
+        );
+
+        assert_eq!(pending(source, "source", target, "target").unwrap(), 2);
+        assert_eq!(sync(source, "source", target, "target").unwrap(), 2);
+        assert_eq!(pending(source, "source", target, "target").unwrap(), 0);
+        assert_eq!(sync(source, "source", target, "target").unwrap(), 0);
+    }
+}
@@ -0,0 +1,212 @@
+use rustic_backend::BackendOptions;
+use rustic_core::{repofile::BlobType, Id, LsOptions, Repository, RepositoryOptions};
+use std::collections::{hash_map, HashMap};
+use std::error::Error;
+use std::hash::Hash;
+
+/// How much of the repository a snapshot takes up.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct SnapshotCost {
+    /// Stored size of the blobs only this snapshot references, freed by forgetting and pruning it.
+    pub unique: u64,
+    /// Stored size of the blobs this snapshot shares with other snapshots.
+    pub shared: u64,
+}
+
+/// Computes the cost of every snapshot from the blobs referenced by its trees.
+pub fn costs(
+    repository: &str,
+    password: &str,
+) -> Result<HashMap<Id, SnapshotCost>, Box<dyn Error>> {
+    let backends = BackendOptions::default()
+        .repository(repository)
+        .to_backends()?;
+
+    let repo_opts = RepositoryOptions::default().password(password);
+
+    // the sizes of blobs are only in the full index
+    let repo = Repository::new(&repo_opts, backends)?
+        .open()?
+        .to_indexed()?;
+
+    // snapshots of unchanged sources share their tree, which only needs to be walked once
+    let mut trees: HashMap<Id, usize> = HashMap::new();
+    let mut snapshots = Vec::new();
+    let mut references = References::default();
+    let size = |blob: &(BlobType, Id)| -> Result<u64, Box<dyn Error>> {
+        Ok(repo.get_index_entry(blob.0, &blob.1)?.length.into())
+    };
+    let ls_opts = LsOptions::default();
+    for snapshot in repo.get_all_snapshots()? {
+        let count = trees.entry(snapshot.tree).or_default();
+        *count += 1;
+        if *count == 1 {
+            let tree = snapshot.tree;
+            references.add(tree, (BlobType::Tree, tree), size)?;
+            let root = repo.node_from_snapshot_and_path(&snapshot, "")?;
+            for entry in repo.ls(&root, &ls_opts)? {
+                let (_, node) = entry?;
+                if let Some(subtree) = node.subtree {
+                    references.add(tree, (BlobType::Tree, subtree), size)?;
+                }
+                for id in node.content.iter().flatten() {
+                    references.add(tree, (BlobType::Data, *id), size)?;
+                }
+            }
+        }
+        snapshots.push((snapshot.id, snapshot.tree));
+    }
+
+    let costs = references.costs();
+    Ok(snapshots
+        .into_iter()
+        .map(|(id, tree)| {
+            let cost = costs.get(&tree).copied().unwrap_or_default();
+            // nothing of a tree referenced by several snapshots is freed by forgetting one
+            let cost = match trees[&tree] {
+                1 => cost,
+                _ => SnapshotCost {
+                    unique: 0,
+                    shared: cost.unique + cost.shared,
+                },
+            };
+            (id, cost)
+        })
+        .collect())
+}
+
+/// Reference counts of the blobs of trees, which are added one tree after the other.
+struct References<B> {
+    /// How many trees reference a blob, the last of them and the blob's stored size.
+    blobs: HashMap<B, (u32, Id, u64)>,
+    /// The stored size of all blobs referenced by each tree.
+    totals: HashMap<Id, u64>,
+}
+
+impl<B> Default for References<B> {
+    fn default() -> Self {
+        Self {
+            blobs: HashMap::new(),
+            totals: HashMap::new(),
+        }
+    }
+}
+
+impl<B: Eq + Hash> References<B> {
+    /// Counts a reference of `tree` to `blob`, unless the tree already referenced it.
+    fn add(
+        &mut self,
+        tree: Id,
+        blob: B,
+        size: impl FnOnce(&B) -> Result<u64, Box<dyn Error>>,
+    ) -> Result<(), Box<dyn Error>> {
+        match self.blobs.entry(blob) {
+            hash_map::Entry::Occupied(mut entry) => {
+                let (count, last, size) = entry.get_mut();
+                if *last != tree {
+                    *count += 1;
+                    *last = tree;
+                    *self.totals.entry(tree).or_default() += *size;
+                }
+            }
+            hash_map::Entry::Vacant(entry) => {
+                let size = size(entry.key())?;
+                entry.insert((1, tree, size));
+                *self.totals.entry(tree).or_default() += size;
+            }
+        }
+        Ok(())
+    }
+
+    /// Splits the size of the blobs of each tree into blobs referenced by it alone and blobs
+    /// referenced by other trees as well.
+    fn costs(&self) -> HashMap<Id, SnapshotCost> {
+        let mut unique: HashMap<Id, u64> = HashMap::new();
+        for (count, tree, size) in self.blobs.values() {
+            if *count == 1 {
+                *unique.entry(*tree).or_default() += size;
+            }
+        }
+
+        self.totals
+            .iter()
+            .map(|(tree, total)| {
+                let unique = unique.get(tree).copied().unwrap_or_default();
+                let shared = total - unique;
+                (*tree, SnapshotCost { unique, shared })
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::backup::{init, snapshot, snapshot::fetch};
+
+    #[test]
+    fn test_references() {
+        let first = Id::random();
+        let second = Id::random();
+        let mut references = References::default();
+        for (tree, blob) in [
+            (first, 1),
+            (first, 2),
+            (second, 2),
+            (second, 3),
+            (second, 4),
+        ] {
+            references.add(tree, blob, |blob| Ok(*blob * 10)).unwrap();
+        }
+        // a blob referenced twice by the same tree is still unique to it
+        references.add(second, 4, |_| unreachable!()).unwrap();
+
+        let costs = references.costs();
+
+        assert_eq!(
+            costs[&first],
+            SnapshotCost {
+                unique: 10,
+                shared: 20
+            }
+        );
+        assert_eq!(
+            costs[&second],
+            SnapshotCost {
+                unique: 70,
+                shared: 20
+            }
+        );
+    }
+
+    #[test]
+    fn test_costs() {
+        let dir = tempfile::tempdir().unwrap();
+        let repository = dir.path().join("repository");
+        let repository = repository.to_str().unwrap();
+        let password = "password";
+        let source = dir.path().join("source");
+        std::fs::create_dir(&source).unwrap();
+
+        assert!(init(repository, password).is_ok());
+        std::fs::write(source.join("shared"), "shared content").unwrap();
+        assert!(snapshot(repository, password, vec![source.to_str().unwrap()]).is_ok());
+        std::fs::write(source.join("unique"), "unique content").unwrap();
+        assert!(snapshot(repository, password, vec![source.to_str().unwrap()]).is_ok());
+
+        let costs = costs(repository, password).unwrap();
+        let snapshots = fetch(repository, password).unwrap();
+        let newest = snapshots.iter().max_by_key(|s| s.time).unwrap();
+
+        assert_eq!(costs.len(), 2);
+        assert!(costs[&newest.id].unique > 0);
+        assert!(costs[&newest.id].shared > 0);
+
+        // an unchanged snapshot shares its whole tree with the previous one
+        assert!(snapshot(repository, password, vec![source.to_str().unwrap()]).is_ok());
+        let costs = super::costs(repository, password).unwrap();
+        assert_eq!(costs.len(), 3);
+        assert_eq!(costs[&newest.id].unique, 0);
+        assert!(costs.values().filter(|cost| cost.unique == 0).count() == 2);
+    }
+}
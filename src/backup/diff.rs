@@ -0,0 +1,331 @@
+use rustic_backend::BackendOptions;
+use rustic_core::{
+    repofile::Node, Id, LocalSource, LocalSourceFilterOptions, LocalSourceSaveOptions, LsOptions,
+    ReadSource, ReadSourceEntry, Repository, RepositoryOptions,
+};
+use std::cmp::Ordering;
+use std::error::Error;
+use std::path::{Path, PathBuf};
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum ChangeKind {
+    Added,
+    Removed,
+    Modified,
+    Metadata,
+}
+
+#[derive(Clone, Debug)]
+pub struct Change {
+    pub path: PathBuf,
+    pub kind: ChangeKind,
+    pub old_size: Option<u64>,
+    pub new_size: Option<u64>,
+}
+
+impl Change {
+    pub fn size_delta(&self) -> i64 {
+        self.new_size.unwrap_or_default() as i64 - self.old_size.unwrap_or_default() as i64
+    }
+}
+
+pub fn diff(
+    repository: &str,
+    password: &str,
+    from: Id,
+    to: Id,
+) -> Result<Vec<Change>, Box<dyn Error>> {
+    let backends = BackendOptions::default()
+        .repository(repository)
+        .to_backends()?;
+
+    let repo_opts = RepositoryOptions::default().password(password);
+
+    let repo = Repository::new(&repo_opts, backends)?
+        .open()?
+        .to_indexed_ids()?;
+
+    let from = repo.get_snapshot_from_str(from.to_hex().as_str(), |_| true)?;
+    let to = repo.get_snapshot_from_str(to.to_hex().as_str(), |_| true)?;
+
+    let from_node = repo.node_from_snapshot_and_path(&from, "")?;
+    let to_node = repo.node_from_snapshot_and_path(&to, "")?;
+
+    // both listings are streamed in tree order, i.e. sorted by path
+    let ls_opts = LsOptions::default();
+    let old = repo
+        .ls(&from_node, &ls_opts)?
+        .collect::<Result<Vec<_>, _>>()?;
+    let new = repo
+        .ls(&to_node, &ls_opts)?
+        .collect::<Result<Vec<_>, _>>()?;
+
+    Ok(compare(old, new, snapshot_change))
+}
+
+/// Compares a snapshot with the current state of its paths on the local filesystem.
+///
+/// Files only present in the snapshot are reported as removed, files only present
+/// on disk as added.
+pub fn diff_live(
+    repository: &str,
+    password: &str,
+    snapshot: Id,
+) -> Result<Vec<Change>, Box<dyn Error>> {
+    let backends = BackendOptions::default()
+        .repository(repository)
+        .to_backends()?;
+
+    let repo_opts = RepositoryOptions::default().password(password);
+
+    let repo = Repository::new(&repo_opts, backends)?
+        .open()?
+        .to_indexed_ids()?;
+
+    let snapshot = repo.get_snapshot_from_str(snapshot.to_hex().as_str(), |_| true)?;
+
+    let ls_opts = LsOptions::default();
+    let mut changes = Vec::new();
+    for path in snapshot.paths.iter() {
+        let node = repo.node_from_snapshot_and_path(&snapshot, path)?;
+        let old = if node.is_dir() {
+            repo.ls(&node, &ls_opts)?
+                .filter(|entry| !matches!(entry, Ok((path, _)) if path.as_os_str().is_empty()))
+                .collect::<Result<Vec<_>, _>>()?
+        } else {
+            vec![(PathBuf::new(), node)]
+        };
+        let new = local_nodes(Path::new(path))?;
+
+        changes.extend(
+            compare(old, new, live_change)
+                .into_iter()
+                .map(|change| Change {
+                    path: join(path, &change.path),
+                    ..change
+                }),
+        );
+    }
+
+    Ok(changes)
+}
+
+/// Lists the nodes below `path` on the local filesystem, relative to `path`.
+fn local_nodes(path: &Path) -> Result<Vec<(PathBuf, Node)>, Box<dyn Error>> {
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+
+    let source = LocalSource::new(
+        LocalSourceSaveOptions::default(),
+        &LocalSourceFilterOptions::default(),
+        &[path],
+    )?;
+
+    let mut nodes = Vec::new();
+    for entry in source.entries() {
+        let ReadSourceEntry {
+            path: entry_path,
+            node,
+            ..
+        } = entry?;
+        let relative = entry_path.strip_prefix(path)?.to_path_buf();
+        if relative.as_os_str().is_empty() && node.is_dir() {
+            continue;
+        }
+        nodes.push((relative, node));
+    }
+
+    Ok(nodes)
+}
+
+fn join(base: &str, relative: &Path) -> PathBuf {
+    if relative.as_os_str().is_empty() {
+        PathBuf::from(base)
+    } else {
+        Path::new(base).join(relative)
+    }
+}
+
+/// Merges two path-sorted node listings into the list of changes between them.
+pub(crate) fn compare(
+    old: Vec<(PathBuf, Node)>,
+    new: Vec<(PathBuf, Node)>,
+    changed: impl Fn(&Node, &Node) -> Option<ChangeKind>,
+) -> Vec<Change> {
+    let mut changes = Vec::new();
+    let mut old = old.into_iter().peekable();
+    let mut new = new.into_iter().peekable();
+
+    loop {
+        let order = match (old.peek(), new.peek()) {
+            (None, None) => break,
+            (Some(_), None) => Ordering::Less,
+            (None, Some(_)) => Ordering::Greater,
+            (Some((old_path, _)), Some((new_path, _))) => old_path.cmp(new_path),
+        };
+
+        match order {
+            Ordering::Less => {
+                let (path, node) = old.next().unwrap();
+                changes.push(Change {
+                    path,
+                    kind: ChangeKind::Removed,
+                    old_size: Some(node.meta.size),
+                    new_size: None,
+                });
+            }
+            Ordering::Greater => {
+                let (path, node) = new.next().unwrap();
+                changes.push(Change {
+                    path,
+                    kind: ChangeKind::Added,
+                    old_size: None,
+                    new_size: Some(node.meta.size),
+                });
+            }
+            Ordering::Equal => {
+                let (path, old_node) = old.next().unwrap();
+                let (_, new_node) = new.next().unwrap();
+                if let Some(kind) = changed(&old_node, &new_node) {
+                    changes.push(Change {
+                        path,
+                        kind,
+                        old_size: Some(old_node.meta.size),
+                        new_size: Some(new_node.meta.size),
+                    });
+                }
+            }
+        }
+    }
+
+    changes
+}
+
+fn snapshot_change(old: &Node, new: &Node) -> Option<ChangeKind> {
+    if old.node_type != new.node_type || old.content != new.content {
+        return Some(ChangeKind::Modified);
+    }
+
+    // directory mtimes change whenever an entry is added or removed, which is already reported
+    let mtime_changed = !old.is_dir() && old.meta.mtime != new.meta.mtime;
+    if mtime_changed
+        || old.meta.mode != new.meta.mode
+        || old.meta.uid != new.meta.uid
+        || old.meta.gid != new.meta.gid
+        || old.meta.user != new.meta.user
+        || old.meta.group != new.meta.group
+    {
+        return Some(ChangeKind::Metadata);
+    }
+
+    None
+}
+
+fn live_change(old: &Node, new: &Node) -> Option<ChangeKind> {
+    if old.node_type != new.node_type {
+        return Some(ChangeKind::Modified);
+    }
+
+    // contents on disk are not chunked, so size and mtime have to do
+    if !old.is_dir() && (old.meta.size != new.meta.size || old.meta.mtime != new.meta.mtime) {
+        return Some(ChangeKind::Modified);
+    }
+
+    if old.meta.mode != new.meta.mode
+        || old.meta.uid != new.meta.uid
+        || old.meta.gid != new.meta.gid
+    {
+        return Some(ChangeKind::Metadata);
+    }
+
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::backup::{init, snapshot, snapshot::fetch};
+    use std::os::unix::fs::PermissionsExt;
+
+    #[test]
+    fn test_diff() {
+        let dir = tempfile::tempdir().unwrap();
+        let repository = dir.path().join("repository");
+        let repository = repository.to_str().unwrap();
+        let password = "password";
+        let source = dir.path().join("source");
+        std::fs::create_dir(&source).unwrap();
+        std::fs::write(source.join("kept"), "kept").unwrap();
+        std::fs::write(source.join("removed"), "removed").unwrap();
+        std::fs::write(source.join("modified"), "old").unwrap();
+        std::fs::write(source.join("chmod"), "chmod").unwrap();
+        let mode = |mode| std::fs::Permissions::from_mode(mode);
+        std::fs::set_permissions(source.join("chmod"), mode(0o644)).unwrap();
+
+        assert!(init(repository, password).is_ok());
+        assert!(snapshot(repository, password, vec![source.to_str().unwrap()]).is_ok());
+        let from = fetch(repository, password).unwrap()[0].id;
+
+        std::fs::write(source.join("added"), "added").unwrap();
+        std::fs::remove_file(source.join("removed")).unwrap();
+        std::fs::write(source.join("modified"), "modified").unwrap();
+        std::fs::set_permissions(source.join("chmod"), mode(0o600)).unwrap();
+        assert!(snapshot(repository, password, vec![source.to_str().unwrap()]).is_ok());
+
+        let to = fetch(repository, password)
+            .unwrap()
+            .into_iter()
+            .find(|snapshot| snapshot.id != from)
+            .unwrap()
+            .id;
+        let changes = diff(repository, password, from, to).unwrap();
+
+        let change = |name: &str| {
+            changes
+                .iter()
+                .find(|change| change.path.ends_with(Path::new("source").join(name)))
+                .map(|change| (change.kind, change.size_delta()))
+        };
+        assert_eq!(change("added"), Some((ChangeKind::Added, 5)));
+        assert_eq!(change("removed"), Some((ChangeKind::Removed, -7)));
+        assert_eq!(change("modified"), Some((ChangeKind::Modified, 5)));
+        assert_eq!(change("chmod"), Some((ChangeKind::Metadata, 0)));
+        assert_eq!(change("kept"), None);
+        assert_eq!(changes.len(), 4);
+
+        assert!(diff(repository, password, to, to).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_diff_live() {
+        let dir = tempfile::tempdir().unwrap();
+        let repository = dir.path().join("repository");
+        let repository = repository.to_str().unwrap();
+        let password = "password";
+        let source = dir.path().join("source");
+        std::fs::create_dir(&source).unwrap();
+        std::fs::write(source.join("kept"), "kept").unwrap();
+        std::fs::write(source.join("deleted"), "deleted").unwrap();
+
+        assert!(init(repository, password).is_ok());
+        assert!(snapshot(repository, password, vec![source.to_str().unwrap()]).is_ok());
+
+        std::fs::remove_file(source.join("deleted")).unwrap();
+        std::fs::write(source.join("created"), "created").unwrap();
+
+        let snapshots = fetch(repository, password).unwrap();
+        let id = snapshots.iter().max_by_key(|s| s.time).unwrap().id;
+        let changes = diff_live(repository, password, id).unwrap();
+
+        let kind = |name: &str| {
+            changes
+                .iter()
+                .find(|change| change.path == source.join(name))
+                .map(|change| change.kind)
+        };
+        assert_eq!(kind("created"), Some(ChangeKind::Added));
+        assert_eq!(kind("deleted"), Some(ChangeKind::Removed));
+        assert_eq!(kind("kept"), None);
+    }
+}
@@ -0,0 +1,297 @@
+use flate2::{write::GzEncoder, Compression};
+use rustic_backend::BackendOptions;
+use rustic_core::{
+    repofile::{Node, NodeType},
+    vfs::OpenFile,
+    IndexedFull, LsOptions, ProgressBars, Repository, RepositoryOptions,
+};
+use std::error::Error;
+use std::fs::File;
+use std::io::{self, BufReader, Read, Seek, Write};
+use std::path::{Path, PathBuf};
+use zip::{write::FileOptions, CompressionMethod, ZipWriter};
+
+/// Files are read from the repository in chunks of this size.
+const READ_BUFFER: usize = 1024 * 1024;
+
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum ArchiveFormat {
+    #[default]
+    Tar,
+    TarGz,
+    TarZst,
+    Zip,
+}
+
+impl ArchiveFormat {
+    pub const ALL: [ArchiveFormat; 4] = [
+        ArchiveFormat::Tar,
+        ArchiveFormat::TarGz,
+        ArchiveFormat::TarZst,
+        ArchiveFormat::Zip,
+    ];
+
+    pub fn extension(&self) -> &'static str {
+        match self {
+            ArchiveFormat::Tar => "tar",
+            ArchiveFormat::TarGz => "tar.gz",
+            ArchiveFormat::TarZst => "tar.zst",
+            ArchiveFormat::Zip => "zip",
+        }
+    }
+}
+
+/// Writes the contents of `snap_path` (`snapshot[:path]`) as an archive to `destination`.
+#[tracing::instrument(skip_all, fields(repo = repository, operation = "export"))]
+pub fn export(
+    repository: &str,
+    password: &str,
+    snap_path: &str,
+    format: ArchiveFormat,
+    destination: &Path,
+) -> Result<(), Box<dyn Error>> {
+    let backends = BackendOptions::default()
+        .repository(repository)
+        .to_backends()?;
+
+    let repo_opts = RepositoryOptions::default().password(password);
+
+    let repo = Repository::new(&repo_opts, backends)?
+        .open()?
+        .to_indexed()?;
+
+    let node = repo.node_from_snapshot_path(snap_path, |_| true)?;
+    let file = File::create(destination)?;
+
+    match format {
+        ArchiveFormat::Tar => {
+            write_tar(&repo, &node, file)?;
+        }
+        ArchiveFormat::TarGz => {
+            let encoder = GzEncoder::new(file, Compression::default());
+            write_tar(&repo, &node, encoder)?.finish()?;
+        }
+        ArchiveFormat::TarZst => {
+            let encoder = zstd::Encoder::new(file, 0)?;
+            write_tar(&repo, &node, encoder)?.finish()?;
+        }
+        ArchiveFormat::Zip => write_zip(&repo, &node, file)?,
+    }
+
+    Ok(())
+}
+
+/// Lists `node` and everything below it, with paths starting at the node's name.
+fn entries<P: ProgressBars, S: IndexedFull>(
+    repo: &Repository<P, S>,
+    node: &Node,
+) -> Result<Vec<(PathBuf, Node)>, Box<dyn Error>> {
+    let root = PathBuf::from(node.name());
+    if !node.is_dir() {
+        return Ok(vec![(root, node.clone())]);
+    }
+
+    let mut entries = Vec::new();
+    if !root.as_os_str().is_empty() {
+        entries.push((root.clone(), node.clone()));
+    }
+    for entry in repo.ls(node, &LsOptions::default())? {
+        let (path, node) = entry?;
+        entries.push((root.join(path), node));
+    }
+
+    Ok(entries)
+}
+
+fn write_tar<P: ProgressBars, S: IndexedFull, W: Write>(
+    repo: &Repository<P, S>,
+    node: &Node,
+    writer: W,
+) -> Result<W, Box<dyn Error>> {
+    let mut builder = tar::Builder::new(writer);
+
+    for (path, node) in entries(repo, node)? {
+        let meta = &node.meta;
+        let mut header = tar::Header::new_gnu();
+        header.set_mode(meta.mode.unwrap_or(0o644) & 0o7777);
+        header.set_uid(meta.uid.unwrap_or_default().into());
+        header.set_gid(meta.gid.unwrap_or_default().into());
+        if let Some(mtime) = meta.mtime {
+            header.set_mtime(mtime.timestamp().max(0) as u64);
+        }
+
+        match &node.node_type {
+            NodeType::Dir => {
+                header.set_entry_type(tar::EntryType::Directory);
+                header.set_size(0);
+                builder.append_data(&mut header, &path, io::empty())?;
+            }
+            NodeType::File => {
+                header.set_entry_type(tar::EntryType::Regular);
+                header.set_size(meta.size);
+                let reader = NodeReader::new(repo, &node)?;
+                builder.append_data(
+                    &mut header,
+                    &path,
+                    BufReader::with_capacity(READ_BUFFER, reader),
+                )?;
+            }
+            NodeType::Symlink { linktarget, .. } => {
+                header.set_entry_type(tar::EntryType::Symlink);
+                header.set_size(0);
+                builder.append_link(&mut header, &path, linktarget)?;
+            }
+            // devices, fifos and sockets carry no data worth archiving
+            _ => {}
+        }
+    }
+
+    Ok(builder.into_inner()?)
+}
+
+fn write_zip<P: ProgressBars, S: IndexedFull, W: Write + Seek>(
+    repo: &Repository<P, S>,
+    node: &Node,
+    writer: W,
+) -> Result<(), Box<dyn Error>> {
+    let mut zip = ZipWriter::new(writer);
+
+    for (path, node) in entries(repo, node)? {
+        let name = path.to_string_lossy();
+        let meta = &node.meta;
+        let options = FileOptions::default()
+            .compression_method(CompressionMethod::Deflated)
+            .unix_permissions(meta.mode.unwrap_or(0o644) & 0o7777)
+            .large_file(meta.size > u64::from(u32::MAX));
+
+        match &node.node_type {
+            NodeType::Dir => zip.add_directory(name, options)?,
+            NodeType::File => {
+                zip.start_file(name, options)?;
+                let mut reader = NodeReader::new(repo, &node)?;
+                io::copy(
+                    &mut BufReader::with_capacity(READ_BUFFER, &mut reader),
+                    &mut zip,
+                )?;
+            }
+            NodeType::Symlink { linktarget, .. } => zip.add_symlink(name, linktarget, options)?,
+            _ => {}
+        }
+    }
+
+    zip.finish()?;
+    Ok(())
+}
+
+/// Streams the contents of a file node from the repository.
+struct NodeReader<'a, P, S> {
+    repo: &'a Repository<P, S>,
+    file: OpenFile,
+    offset: usize,
+    size: usize,
+}
+
+impl<'a, P: ProgressBars, S: IndexedFull> NodeReader<'a, P, S> {
+    fn new(repo: &'a Repository<P, S>, node: &Node) -> Result<Self, Box<dyn Error>> {
+        Ok(Self {
+            repo,
+            file: repo.open_file(node)?,
+            offset: 0,
+            size: node.meta.size as usize,
+        })
+    }
+}
+
+impl<P: ProgressBars, S: IndexedFull> Read for NodeReader<'_, P, S> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let length = buf.len().min(self.size - self.offset);
+        if length == 0 {
+            return Ok(0);
+        }
+
+        let data = self
+            .repo
+            .read_file_at(&self.file, self.offset, length)
+            .map_err(io::Error::other)?;
+        buf[..data.len()].copy_from_slice(&data);
+        self.offset += data.len();
+
+        Ok(data.len())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::backup::{init, snapshot, snapshot::fetch};
+    use std::collections::BTreeMap;
+
+    /// The entries of an archive by path, with the contents of files and `None` for directories.
+    fn read_archive(path: &Path, format: ArchiveFormat) -> BTreeMap<String, Option<String>> {
+        let file = File::open(path).unwrap();
+        let mut entries = BTreeMap::new();
+        if format == ArchiveFormat::Zip {
+            let mut archive = zip::ZipArchive::new(file).unwrap();
+            for index in 0..archive.len() {
+                let mut entry = archive.by_index(index).unwrap();
+                let name = entry.name().trim_end_matches('/').to_string();
+                let content = entry.is_file().then(|| {
+                    let mut content = String::new();
+                    entry.read_to_string(&mut content).unwrap();
+                    content
+                });
+                entries.insert(name, content);
+            }
+            return entries;
+        }
+
+        let reader: Box<dyn Read> = match format {
+            ArchiveFormat::TarGz => Box::new(flate2::read::GzDecoder::new(file)),
+            ArchiveFormat::TarZst => Box::new(zstd::Decoder::new(file).unwrap()),
+            _ => Box::new(file),
+        };
+        let mut archive = tar::Archive::new(reader);
+        for entry in archive.entries().unwrap() {
+            let mut entry = entry.unwrap();
+            let name = entry.path().unwrap().to_string_lossy().to_string();
+            let content = entry.header().entry_type().is_file().then(|| {
+                let mut content = String::new();
+                entry.read_to_string(&mut content).unwrap();
+                content
+            });
+            entries.insert(name, content);
+        }
+        entries
+    }
+
+    #[test]
+    fn test_export() {
+        let dir = tempfile::tempdir().unwrap();
+        let repository = dir.path().join("repository");
+        let repository = repository.to_str().unwrap();
+        let password = "password";
+        let source = dir.path().join("source");
+        std::fs::create_dir_all(source.join("dir")).unwrap();
+        std::fs::write(source.join("file"), "content").unwrap();
+        std::fs::write(source.join("dir").join("nested"), "nested").unwrap();
+
+        assert!(init(repository, password).is_ok());
+        assert!(snapshot(repository, password, vec![source.to_str().unwrap()]).is_ok());
+
+        let snapshots = fetch(repository, password).unwrap();
+        let id = snapshots.iter().max_by_key(|s| s.time).unwrap().id;
+        let snap_path = format!("{}:{}", id.to_hex().as_str(), source.display());
+
+        let expected = BTreeMap::from([
+            ("source".to_string(), None),
+            ("source/dir".to_string(), None),
+            ("source/dir/nested".to_string(), Some("nested".to_string())),
+            ("source/file".to_string(), Some("content".to_string())),
+        ]);
+        for format in ArchiveFormat::ALL {
+            let destination = dir.path().join(format!("export.{}", format.extension()));
+            assert!(export(repository, password, &snap_path, format, &destination).is_ok());
+            assert_eq!(read_archive(&destination, format), expected, "{format:?}");
+        }
+    }
+}
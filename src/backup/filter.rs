@@ -0,0 +1,182 @@
+use chrono::{Local, NaiveDate};
+use rustic_core::{repofile::SnapshotFile, StringList};
+use serde::{Deserialize, Serialize};
+
+const DATE_FORMAT: &str = "%Y-%m-%d";
+
+/// Narrows down the snapshot list, following rustic's `--filter-*` options.
+///
+/// Every field holds the text as entered, so it can be stored in the config and shown again:
+/// - `hosts`: comma separated, a snapshot matches any of them.
+/// - `tags` and `paths`: comma separated lists separated by spaces, a snapshot matches if it
+///   contains all entries of any list, so `a,b c` matches snapshots tagged `a` and `b` or `c`.
+/// - `after` and `before`: dates in `YYYY-MM-DD` format, both inclusive.
+/// - `text`: matched case-insensitively against the id, host, paths, tags and label.
+#[derive(Clone, Debug, Default, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SnapshotFilter {
+    pub hosts: String,
+    pub tags: String,
+    pub paths: String,
+    pub after: String,
+    pub before: String,
+    pub text: String,
+}
+
+impl SnapshotFilter {
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+
+    /// Whether the date fields are empty or can be parsed.
+    pub fn is_valid(&self) -> bool {
+        [&self.after, &self.before]
+            .iter()
+            .all(|date| date.trim().is_empty() || parse_date(date).is_some())
+    }
+
+    pub fn matches(&self, snapshot: &SnapshotFile) -> bool {
+        let hosts = split(&self.hosts, ',');
+        if !hosts.is_empty() && !hosts.contains(&snapshot.hostname.as_str()) {
+            return false;
+        }
+
+        if !contains_any_list(&snapshot.tags, &self.tags)
+            || !contains_any_list(&snapshot.paths, &self.paths)
+        {
+            return false;
+        }
+
+        let date = snapshot.time.with_timezone(&Local).date_naive();
+        if parse_date(&self.after).is_some_and(|after| date < after)
+            || parse_date(&self.before).is_some_and(|before| date > before)
+        {
+            return false;
+        }
+
+        let text = self.text.trim().to_lowercase();
+        text.is_empty()
+            || [
+                snapshot.id.to_hex().as_str().to_string(),
+                snapshot.hostname.clone(),
+                snapshot.label.clone(),
+            ]
+            .iter()
+            .chain(snapshot.paths.iter())
+            .chain(snapshot.tags.iter())
+            .any(|field| field.to_lowercase().contains(&text))
+    }
+}
+
+fn split(value: &str, separator: char) -> Vec<&str> {
+    value
+        .split(separator)
+        .map(str::trim)
+        .filter(|entry| !entry.is_empty())
+        .collect()
+}
+
+fn contains_any_list(values: &StringList, lists: &str) -> bool {
+    let lists: Vec<Vec<&str>> = lists
+        .split_whitespace()
+        .map(|list| split(list, ','))
+        .collect();
+
+    lists.is_empty()
+        || lists
+            .iter()
+            .any(|list| list.iter().all(|entry| values.iter().any(|v| v == entry)))
+}
+
+fn parse_date(value: &str) -> Option<NaiveDate> {
+    NaiveDate::parse_from_str(value.trim(), DATE_FORMAT).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn snapshot(hostname: &str, tags: &[&str], paths: &[&str]) -> SnapshotFile {
+        let mut snapshot = SnapshotFile {
+            hostname: hostname.to_string(),
+            time: Local.with_ymd_and_hms(2024, 5, 10, 12, 0, 0).unwrap(),
+            ..Default::default()
+        };
+        for tag in tags {
+            snapshot.tags.add(tag.to_string());
+        }
+        for path in paths {
+            snapshot.paths.add(path.to_string());
+        }
+        snapshot
+    }
+
+    #[test]
+    fn test_filter_empty() {
+        let filter = SnapshotFilter::default();
+
+        assert!(filter.is_empty());
+        assert!(filter.matches(&snapshot("laptop", &[], &["/home"])));
+    }
+
+    #[test]
+    fn test_filter_hosts() {
+        let filter = SnapshotFilter {
+            hosts: "laptop, desktop".into(),
+            ..Default::default()
+        };
+
+        assert!(filter.matches(&snapshot("desktop", &[], &[])));
+        assert!(!filter.matches(&snapshot("server", &[], &[])));
+    }
+
+    #[test]
+    fn test_filter_tags() {
+        let filter = SnapshotFilter {
+            tags: "daily,home weekly".into(),
+            ..Default::default()
+        };
+
+        assert!(filter.matches(&snapshot("laptop", &["daily", "home"], &[])));
+        assert!(filter.matches(&snapshot("laptop", &["weekly"], &[])));
+        assert!(!filter.matches(&snapshot("laptop", &["daily"], &[])));
+    }
+
+    #[test]
+    fn test_filter_paths() {
+        let filter = SnapshotFilter {
+            paths: "/home".into(),
+            ..Default::default()
+        };
+
+        assert!(filter.matches(&snapshot("laptop", &[], &["/etc", "/home"])));
+        assert!(!filter.matches(&snapshot("laptop", &[], &["/etc"])));
+    }
+
+    #[test]
+    fn test_filter_dates() {
+        let snapshot = snapshot("laptop", &[], &[]);
+        let filter = |after: &str, before: &str| SnapshotFilter {
+            after: after.into(),
+            before: before.into(),
+            ..Default::default()
+        };
+
+        assert!(filter("2024-05-10", "2024-05-10").matches(&snapshot));
+        assert!(!filter("2024-05-11", "").matches(&snapshot));
+        assert!(!filter("", "2024-05-09").matches(&snapshot));
+        assert!(!filter("yesterday", "").is_valid());
+    }
+
+    #[test]
+    fn test_filter_text() {
+        let filter = SnapshotFilter {
+            text: "HOME".into(),
+            ..Default::default()
+        };
+
+        assert!(filter.matches(&snapshot("laptop", &[], &["/home"])));
+        assert!(!filter.matches(&snapshot("laptop", &[], &["/etc"])));
+    }
+}
@@ -0,0 +1,318 @@
+use chrono::{DateTime, Local};
+use globset::{GlobBuilder, GlobMatcher};
+use rustic_backend::BackendOptions;
+use rustic_core::{repofile::Node, Id, LsOptions, Repository, RepositoryOptions};
+use std::cmp::Reverse;
+use std::collections::{hash_map, HashMap};
+use std::error::Error;
+use std::path::{Component, Path, PathBuf};
+
+#[derive(Clone, Debug)]
+pub struct Match {
+    pub snapshot: Id,
+    pub time: DateTime<Local>,
+    pub path: PathBuf,
+    pub size: u64,
+    pub mtime: Option<DateTime<Local>>,
+}
+
+impl Match {
+    /// The `snapshot:path` string understood by [`crate::backup::restore::restore`].
+    pub fn snapshot_path(&self) -> String {
+        format!(
+            "{}:{}",
+            self.snapshot.to_hex().as_str(),
+            self.path.display()
+        )
+    }
+}
+
+/// A content of a file, with the time ranges of the snapshots containing it.
+#[derive(Clone, Debug)]
+pub struct Version {
+    pub path: PathBuf,
+    pub content: Vec<Id>,
+    pub size: u64,
+    pub mtime: Option<DateTime<Local>>,
+    /// The first and last time of each run of consecutive snapshots containing this content,
+    /// oldest first.
+    pub ranges: Vec<(DateTime<Local>, DateTime<Local>)>,
+    /// The newest snapshot containing this version.
+    pub snapshot: Id,
+    pub snapshot_count: usize,
+}
+
+impl Version {
+    pub fn last_seen(&self) -> DateTime<Local> {
+        self.ranges
+            .last()
+            .map(|(_, last)| *last)
+            .unwrap_or_default()
+    }
+
+    /// The `snapshot:path` string understood by [`crate::backup::restore::restore`].
+    pub fn snapshot_path(&self) -> String {
+        format!(
+            "{}:{}",
+            self.snapshot.to_hex().as_str(),
+            self.path.display()
+        )
+    }
+}
+
+/// Matches paths either by glob, if the query contains glob syntax, or by substring.
+///
+/// Queries without a `/` are matched against the file name only. In globs `*` stays within one
+/// directory, `**` matches across directories.
+pub enum Matcher {
+    Glob {
+        matcher: GlobMatcher,
+        name_only: bool,
+    },
+    Substring(String),
+}
+
+impl Matcher {
+    pub fn new(query: &str) -> Result<Self, Box<dyn Error>> {
+        if query.contains(['*', '?', '[', '{']) {
+            Ok(Matcher::Glob {
+                matcher: GlobBuilder::new(query)
+                    .literal_separator(true)
+                    .build()?
+                    .compile_matcher(),
+                name_only: !query.contains('/'),
+            })
+        } else {
+            Ok(Matcher::Substring(query.to_lowercase()))
+        }
+    }
+
+    pub fn is_match(&self, path: &Path) -> bool {
+        match self {
+            Matcher::Glob { matcher, name_only } => match (name_only, path.file_name()) {
+                (true, Some(name)) => matcher.is_match(name),
+                (true, None) => false,
+                (false, _) => matcher.is_match(path),
+            },
+            Matcher::Substring(query) => path
+                .to_string_lossy()
+                .to_lowercase()
+                .contains(query.as_str()),
+        }
+    }
+}
+
+/// A file matching a query in a tree: its path, size and modification time.
+type Found = (PathBuf, u64, Option<DateTime<Local>>);
+
+/// Searches all snapshots of a repository for files matching `query`, newest snapshot first.
+pub fn find(repository: &str, password: &str, query: &str) -> Result<Vec<Match>, Box<dyn Error>> {
+    let matcher = Matcher::new(query)?;
+
+    let backends = BackendOptions::default()
+        .repository(repository)
+        .to_backends()?;
+
+    let repo_opts = RepositoryOptions::default().password(password);
+
+    let repo = Repository::new(&repo_opts, backends)?
+        .open()?
+        .to_indexed_ids()?;
+
+    let mut snapshots = repo.get_all_snapshots()?;
+    snapshots.sort_unstable_by_key(|snapshot| Reverse(snapshot.time));
+
+    // snapshots of unchanged sources share their tree, which only needs to be walked once
+    let mut trees: HashMap<Id, Vec<Found>> = HashMap::new();
+    let ls_opts = LsOptions::default();
+    let mut matches = Vec::new();
+    for snapshot in snapshots {
+        if let hash_map::Entry::Vacant(tree) = trees.entry(snapshot.tree) {
+            let root = repo.node_from_snapshot_and_path(&snapshot, "")?;
+            let mut found = Vec::new();
+            for entry in repo.ls(&root, &ls_opts)? {
+                let (path, node) = entry?;
+                let path = Path::new("/").join(path);
+                if !node.is_dir() && matcher.is_match(&path) {
+                    found.push((path, node.meta.size, node.meta.mtime));
+                }
+            }
+            tree.insert(found);
+        }
+
+        matches.extend(
+            trees[&snapshot.tree]
+                .iter()
+                .map(|(path, size, mtime)| Match {
+                    snapshot: snapshot.id,
+                    time: snapshot.time,
+                    path: path.clone(),
+                    size: *size,
+                    mtime: *mtime,
+                }),
+        );
+    }
+
+    Ok(matches)
+}
+
+/// Lists the versions of the file at `path` stored in the repository, newest first.
+///
+/// Versions are told apart by content, so a content that comes back after the file was changed or
+/// missing adds another time range to its earlier version.
+pub fn versions(
+    repository: &str,
+    password: &str,
+    path: &Path,
+) -> Result<Vec<Version>, Box<dyn Error>> {
+    let backends = BackendOptions::default()
+        .repository(repository)
+        .to_backends()?;
+
+    let repo_opts = RepositoryOptions::default().password(password);
+
+    let repo = Repository::new(&repo_opts, backends)?
+        .open()?
+        .to_indexed_ids()?;
+
+    let mut snapshots = repo.get_all_snapshots()?;
+    snapshots.sort_unstable_by_key(|snapshot| snapshot.time);
+
+    // walks the trees by hand, so only a missing path is told apart from read errors
+    let node_at = |root: Node| -> Result<Option<Node>, Box<dyn Error>> {
+        let mut node = root;
+        for component in path.components() {
+            let Component::Normal(name) = component else {
+                continue;
+            };
+            let Some(subtree) = node.subtree else {
+                return Ok(None);
+            };
+            let child = repo
+                .get_tree(&subtree)?
+                .nodes
+                .into_iter()
+                .find(|child| child.name().as_os_str() == name);
+            match child {
+                Some(child) => node = child,
+                None => return Ok(None),
+            }
+        }
+        Ok(Some(node))
+    };
+
+    let mut versions: Vec<Version> = Vec::new();
+    let mut by_content: HashMap<Vec<Id>, usize> = HashMap::new();
+    // the version in the previous snapshot, whose last range a later snapshot can extend
+    let mut previous = None;
+    for snapshot in snapshots {
+        let root = repo.node_from_snapshot_and_path(&snapshot, "")?;
+        let node = match node_at(root)? {
+            Some(node) if !node.is_dir() => node,
+            _ => {
+                previous = None;
+                continue;
+            }
+        };
+
+        let content = node.content.unwrap_or_default();
+        let index = *by_content.entry(content.clone()).or_insert_with(|| {
+            versions.push(Version {
+                path: path.to_path_buf(),
+                content,
+                size: node.meta.size,
+                mtime: node.meta.mtime,
+                ranges: Vec::new(),
+                snapshot: snapshot.id,
+                snapshot_count: 0,
+            });
+            versions.len() - 1
+        });
+
+        let version = &mut versions[index];
+        match version.ranges.last_mut() {
+            Some((_, last)) if previous == Some(index) => *last = snapshot.time,
+            _ => version.ranges.push((snapshot.time, snapshot.time)),
+        }
+        version.mtime = node.meta.mtime;
+        version.snapshot = snapshot.id;
+        version.snapshot_count += 1;
+        previous = Some(index);
+    }
+
+    versions.sort_unstable_by_key(|version| Reverse(version.last_seen()));
+    Ok(versions)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_matcher_substring() {
+        let matcher = Matcher::new("Host").unwrap();
+
+        assert!(matcher.is_match(Path::new("/etc/hostname")));
+        assert!(!matcher.is_match(Path::new("/etc/passwd")));
+    }
+
+    #[test]
+    fn test_matcher_glob() {
+        let name = Matcher::new("*.conf").unwrap();
+        let path = Matcher::new("/etc/*.conf").unwrap();
+
+        assert!(name.is_match(Path::new("/etc/nested/resolv.conf")));
+        assert!(path.is_match(Path::new("/etc/resolv.conf")));
+        assert!(!path.is_match(Path::new("/home/resolv.conf")));
+        assert!(!path.is_match(Path::new("/etc/nested/resolv.conf")));
+
+        let nested = Matcher::new("docs/*.txt").unwrap();
+        assert!(nested.is_match(Path::new("docs/c.txt")));
+        assert!(!nested.is_match(Path::new("docs/a/b/c.txt")));
+        let recursive = Matcher::new("docs/**/*.txt").unwrap();
+        assert!(recursive.is_match(Path::new("docs/a/b/c.txt")));
+    }
+
+    #[test]
+    fn test_versions() {
+        let dir = tempfile::tempdir().unwrap();
+        let repository = dir.path().join("repository");
+        let repository = repository.to_str().unwrap();
+        let password = "password";
+        let source = dir.path().join("source");
+        let file = source.join("file");
+        std::fs::create_dir(&source).unwrap();
+
+        assert!(crate::backup::init(repository, password).is_ok());
+        let snapshot = |content: &str| {
+            std::fs::write(&file, content).unwrap();
+            let paths = vec![source.to_str().unwrap()];
+            assert!(crate::backup::snapshot(repository, password, paths).is_ok());
+        };
+        snapshot("first");
+        snapshot("first");
+        snapshot("second");
+
+        let found = versions(repository, password, &file).unwrap();
+
+        assert_eq!(found.len(), 2);
+        assert_eq!(found[0].size, "second".len() as u64);
+        assert_eq!(found[0].snapshot_count, 1);
+        assert_eq!(found[1].snapshot_count, 2);
+
+        // the first content coming back adds a time range to its version
+        snapshot("first");
+        let found = versions(repository, password, &file).unwrap();
+        assert_eq!(found.len(), 2);
+        assert_eq!(found[0].size, "first".len() as u64);
+        assert_eq!(found[0].snapshot_count, 3);
+        assert_eq!(found[0].ranges.len(), 2);
+        assert_eq!(found[1].ranges.len(), 1);
+        let (first, last) = (found[0].ranges[0], found[0].ranges[1]);
+        assert!(first.1 < found[1].ranges[0].0);
+        assert!(found[1].ranges[0].1 < last.0);
+
+        let missing = source.join("missing");
+        assert!(versions(repository, password, &missing).unwrap().is_empty());
+    }
+}
@@ -0,0 +1,107 @@
+use chrono::{DateTime, Duration, Local};
+use std::path::Path;
+
+use crate::backup::history::{self, Operation, Run};
+
+/// Repositories whose last backup is older than this many days are stale, unless configured
+/// otherwise.
+pub const DEFAULT_STALE_DAYS: u32 = 7;
+
+/// How well a repository is backed up, from its run history.
+///
+/// The variants are ordered by severity, the most severe problem is reported.
+#[derive(Clone, Copy, Debug, Eq, Ord, PartialEq, PartialOrd)]
+pub enum Health {
+    /// Nothing was backed up to the repository with Stellarshot yet.
+    Unknown,
+    Ok,
+    /// The last successful backup is older than the threshold.
+    Stale,
+    /// The last backup failed.
+    Failed,
+    /// The last check found errors.
+    CheckErrors,
+    /// The repository cannot be found, e.g. because its drive is not connected.
+    Unavailable,
+}
+
+/// The health of the repository at `repository`, `stale_days` of 0 means [`DEFAULT_STALE_DAYS`].
+pub fn health(repository: &Path, stale_days: u32) -> Health {
+    if !repository.exists() {
+        return Health::Unavailable;
+    }
+    let runs = match history::runs(repository) {
+        Ok(runs) => runs,
+        Err(err) => {
+            log::warn!(
+                "failed to read the history of {}: {}",
+                repository.display(),
+                err
+            );
+            return Health::Unknown;
+        }
+    };
+    let stale_after = match stale_days {
+        0 => DEFAULT_STALE_DAYS,
+        days => days,
+    };
+    status(&runs, Duration::days(stale_after.into()), Local::now())
+}
+
+/// The health shown by `runs`, newest first.
+fn status(runs: &[Run], stale_after: Duration, now: DateTime<Local>) -> Health {
+    let last = |operation| runs.iter().find(|run| run.operation == operation);
+
+    let check = match last(Operation::Check) {
+        Some(check) if !check.succeeded() => Health::CheckErrors,
+        _ => Health::Unknown,
+    };
+    let backup = match last(Operation::Backup) {
+        None => Health::Unknown,
+        Some(backup) if !backup.succeeded() => Health::Failed,
+        Some(backup) if now - backup.finished > stale_after => Health::Stale,
+        Some(_) => Health::Ok,
+    };
+    check.max(backup)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn run(operation: Operation, days_ago: i64, error: Option<&str>) -> Run {
+        let mut run = Run::start(Path::new("/tmp/repo"), operation, None);
+        run.finish(error.map(String::from));
+        run.started -= Duration::days(days_ago);
+        run.finished -= Duration::days(days_ago);
+        run
+    }
+
+    #[test]
+    fn test_status() {
+        let week = Duration::days(7);
+        let now = Local::now();
+
+        assert_eq!(status(&[], week, now), Health::Unknown);
+        let ok = run(Operation::Backup, 1, None);
+        assert_eq!(status(std::slice::from_ref(&ok), week, now), Health::Ok);
+        let old = run(Operation::Backup, 10, None);
+        assert_eq!(status(std::slice::from_ref(&old), week, now), Health::Stale);
+
+        let failed = run(Operation::Backup, 0, Some("no space left"));
+        assert_eq!(
+            status(&[failed.clone(), ok.clone()], week, now),
+            Health::Failed
+        );
+        // a successful backup after a failed one fixes it
+        assert_eq!(status(&[ok.clone(), failed], week, now), Health::Ok);
+
+        let check = run(Operation::Check, 0, Some("pack missing"));
+        assert_eq!(
+            status(&[check.clone(), old], week, now),
+            Health::CheckErrors
+        );
+        let passed = run(Operation::Check, 0, None);
+        assert_eq!(status(&[passed, check, ok], week, now), Health::Ok);
+    }
+}
@@ -0,0 +1,252 @@
+use chrono::{DateTime, Local};
+use once_cell::sync::Lazy;
+use rustic_core::repofile::SnapshotFile;
+use serde::{Deserialize, Serialize};
+use std::env;
+use std::error::Error;
+use std::fs::{self, File, OpenOptions};
+use std::io::{BufRead, BufReader, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+/// File in the state directory the runs are appended to, one JSON object per line.
+const HISTORY_FILE: &str = "history.jsonl";
+
+/// The runs read from the history file so far, so later reads only parse the runs appended since.
+static CACHE: Lazy<Mutex<Cache>> = Lazy::new(Mutex::default);
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub enum Operation {
+    Backup,
+    Prune,
+    Check,
+}
+
+/// What a backup stored, taken from the snapshot's summary.
+#[derive(Clone, Debug, Default, Eq, PartialEq, Serialize, Deserialize)]
+pub struct RunSummary {
+    /// The snapshot unchanged files were taken from, `None` for a full scan.
+    #[serde(default)]
+    pub parent: Option<String>,
+    pub files_new: u64,
+    pub files_changed: u64,
+    pub files_unmodified: u64,
+    pub bytes_processed: u64,
+    pub data_added: u64,
+}
+
+/// A backup, prune or check that ran on a repository.
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub struct Run {
+    pub repository: PathBuf,
+    /// The profile a backup was made with, if any.
+    pub profile: Option<String>,
+    pub operation: Operation,
+    pub started: DateTime<Local>,
+    pub finished: DateTime<Local>,
+    /// Why the run failed, `None` if it succeeded.
+    pub error: Option<String>,
+    pub snapshot: Option<String>,
+    pub summary: Option<RunSummary>,
+    /// Output of hooks and other messages collected during the run.
+    pub log: String,
+}
+
+impl Run {
+    /// Starts recording a run now.
+    pub fn start(repository: &Path, operation: Operation, profile: Option<String>) -> Self {
+        let now = Local::now();
+        Self {
+            repository: repository.to_path_buf(),
+            profile,
+            operation,
+            started: now,
+            finished: now,
+            error: None,
+            snapshot: None,
+            summary: None,
+            log: String::new(),
+        }
+    }
+
+    pub fn set_snapshot(&mut self, snapshot: &SnapshotFile) {
+        self.snapshot = Some(snapshot.id.to_hex().to_string());
+        self.summary = snapshot.summary.as_ref().map(|summary| RunSummary {
+            parent: snapshot.parent.map(|parent| parent.to_hex().to_string()),
+            files_new: summary.files_new,
+            files_changed: summary.files_changed,
+            files_unmodified: summary.files_unmodified,
+            bytes_processed: summary.total_bytes_processed,
+            data_added: summary.data_added,
+        });
+    }
+
+    /// Ends a backup run with its result.
+    pub fn finish_backup(&mut self, result: &Result<SnapshotFile, Box<dyn Error>>) {
+        match result {
+            Ok(snapshot) => {
+                self.set_snapshot(snapshot);
+                self.finish(None);
+            }
+            Err(err) => self.finish(Some(err.to_string())),
+        }
+    }
+
+    /// Ends the run now, with the error it failed with.
+    pub fn finish(&mut self, error: Option<String>) {
+        self.finished = Local::now();
+        self.error = error;
+    }
+
+    pub fn succeeded(&self) -> bool {
+        self.error.is_none()
+    }
+}
+
+/// Where Stellarshot keeps its history and logs, `$XDG_STATE_HOME/stellarshot`.
+pub fn state_dir() -> PathBuf {
+    let state_home = env::var_os("XDG_STATE_HOME")
+        .filter(|dir| !dir.is_empty())
+        .map(PathBuf::from)
+        .unwrap_or_else(|| {
+            let home = env::var_os("HOME").map(PathBuf::from).unwrap_or_default();
+            home.join(".local/state")
+        });
+    state_home.join("stellarshot")
+}
+
+/// Adds a finished run to the history.
+pub fn record(run: &Run) -> Result<(), Box<dyn Error>> {
+    let dir = state_dir();
+    fs::create_dir_all(&dir)?;
+    append(&dir.join(HISTORY_FILE), run)
+}
+
+/// The recorded runs of a repository, newest first.
+pub fn runs(repository: &Path) -> Result<Vec<Run>, Box<dyn Error>> {
+    let path = state_dir().join(HISTORY_FILE);
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+    let mut cache = CACHE.lock().unwrap();
+    cache.update(&path)?;
+    Ok(cache
+        .runs
+        .iter()
+        .rev()
+        .filter(|run| run.repository == repository)
+        .cloned()
+        .collect())
+}
+
+/// Writes `runs` to `destination` as a JSON array.
+pub fn export(runs: &[Run], destination: &Path) -> Result<(), Box<dyn Error>> {
+    let file = File::create(destination)?;
+    serde_json::to_writer_pretty(file, runs)?;
+    Ok(())
+}
+
+fn append(path: &Path, run: &Run) -> Result<(), Box<dyn Error>> {
+    let mut file = OpenOptions::new().create(true).append(true).open(path)?;
+    let mut line = serde_json::to_vec(run)?;
+    line.push(b'\n');
+    file.write_all(&line)?;
+    Ok(())
+}
+
+#[derive(Default)]
+struct Cache {
+    path: PathBuf,
+    /// How many bytes of the file the runs were read from.
+    offset: u64,
+    runs: Vec<Run>,
+}
+
+impl Cache {
+    fn update(&mut self, path: &Path) -> Result<(), Box<dyn Error>> {
+        // another or a truncated file is read from the start again
+        if self.path != path || fs::metadata(path)?.len() < self.offset {
+            *self = Cache {
+                path: path.to_path_buf(),
+                ..Default::default()
+            };
+        }
+        let (runs, offset) = read(path, self.offset)?;
+        self.runs.extend(runs);
+        self.offset = offset;
+        Ok(())
+    }
+}
+
+/// Reads the runs recorded after `offset` in the order they were recorded, skipping lines that
+/// cannot be parsed. Returns them with the offset of the first line not read yet.
+fn read(path: &Path, offset: u64) -> Result<(Vec<Run>, u64), Box<dyn Error>> {
+    let mut reader = BufReader::new(File::open(path)?);
+    reader.seek(SeekFrom::Start(offset))?;
+
+    let mut offset = offset;
+    let mut runs = Vec::new();
+    let mut line = String::new();
+    loop {
+        line.clear();
+        let length = reader.read_line(&mut line)?;
+        // a line still being written is read again next time
+        if length == 0 || !line.ends_with('\n') {
+            break;
+        }
+        offset += length as u64;
+        match serde_json::from_str(line.trim_end()) {
+            Ok(run) => runs.push(run),
+            Err(err) => log::warn!("skipping history entry {:?}: {}", line, err),
+        }
+    }
+    Ok((runs, offset))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_history() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join(HISTORY_FILE);
+
+        let mut backup = Run::start(
+            Path::new("/tmp/repo"),
+            Operation::Backup,
+            Some("home".into()),
+        );
+        backup.log.push_str("pre-backup hook `true`: succeeded\n");
+        backup.finish(None);
+        let mut check = Run::start(Path::new("/tmp/repo"), Operation::Check, None);
+        check.finish(Some("pack missing".into()));
+        append(&path, &backup).unwrap();
+        append(&path, &check).unwrap();
+
+        let (runs, _) = read(&path, 0).unwrap();
+        assert_eq!(runs, [backup.clone(), check.clone()]);
+        assert!(runs[0].succeeded());
+        assert!(!runs[1].succeeded());
+
+        let exported = dir.path().join("history.json");
+        export(&runs[1..], &exported).unwrap();
+        let exported: Vec<Run> = serde_json::from_reader(File::open(&exported).unwrap()).unwrap();
+        assert_eq!(exported, [check.clone()]);
+
+        // only runs appended since the last read are parsed
+        let mut cache = Cache::default();
+        cache.update(&path).unwrap();
+        append(&path, &backup).unwrap();
+        let length = fs::metadata(&path).unwrap().len();
+        fs::OpenOptions::new()
+            .append(true)
+            .open(&path)
+            .unwrap()
+            .write_all(b"{\"incomplete")
+            .unwrap();
+        cache.update(&path).unwrap();
+        assert_eq!(cache.runs, [backup.clone(), check, backup]);
+        assert_eq!(cache.offset, length);
+    }
+}
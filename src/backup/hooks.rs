@@ -0,0 +1,300 @@
+use serde::{Deserialize, Serialize};
+use std::error::Error;
+use std::fmt;
+use std::fs::{self, File};
+use std::io::{self, Read, Seek, SeekFrom};
+use std::os::unix::process::CommandExt;
+use std::process::{Command, Stdio};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// Hooks without a configured timeout are stopped after this many seconds.
+pub const DEFAULT_TIMEOUT: u64 = 300;
+
+/// How often a running hook is checked for having exited.
+const POLL_INTERVAL: Duration = Duration::from_millis(50);
+
+/// Only the end of a hook's output is kept, as it is stored with every recorded run.
+const MAX_OUTPUT: u64 = 4 * 1024;
+
+/// Shell commands run around a backup, empty commands are skipped.
+///
+/// The commands get the variables `STELLARSHOT_PROFILE` and `STELLARSHOT_REPOSITORY`, the failure
+/// hook also `STELLARSHOT_ERROR`.
+#[derive(Clone, Debug, Default, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Hooks {
+    /// Runs before the backup, which is skipped if it fails.
+    pub pre: String,
+    /// Runs after a successful backup.
+    pub post: String,
+    /// Runs if the backup or one of the other hooks failed.
+    pub failure: String,
+    /// Seconds after which a hook is killed, 0 for [`DEFAULT_TIMEOUT`].
+    pub timeout: u64,
+}
+
+impl Hooks {
+    pub fn is_empty(&self) -> bool {
+        self.pre.trim().is_empty() && self.post.trim().is_empty() && self.failure.trim().is_empty()
+    }
+
+    fn timeout(&self) -> Duration {
+        match self.timeout {
+            0 => Duration::from_secs(DEFAULT_TIMEOUT),
+            timeout => Duration::from_secs(timeout),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Stage {
+    Pre,
+    Post,
+    Failure,
+}
+
+impl fmt::Display for Stage {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Stage::Pre => write!(f, "pre-backup"),
+            Stage::Post => write!(f, "post-backup"),
+            Stage::Failure => write!(f, "on-failure"),
+        }
+    }
+}
+
+/// A hook that ran, with the end of what it wrote to stdout and stderr.
+#[derive(Clone, Debug)]
+pub struct HookRun {
+    pub stage: Stage,
+    pub command: String,
+    pub output: String,
+    pub result: Result<(), String>,
+}
+
+impl fmt::Display for HookRun {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let result = match &self.result {
+            Ok(()) => "succeeded",
+            Err(err) => err,
+        };
+        writeln!(f, "{} hook `{}`: {}", self.stage, self.command, result)?;
+        for line in self.output.lines() {
+            writeln!(f, "  {line}")?;
+        }
+        Ok(())
+    }
+}
+
+/// Runs `backup` between the hooks.
+///
+/// A failing pre-backup hook aborts the run. A failing post-backup hook fails the run as well,
+/// though the snapshot is kept. The failure hook runs once for any of these.
+pub fn backup_with_hooks<T>(
+    hooks: &Hooks,
+    env: &[(&str, &str)],
+    backup: impl FnOnce() -> Result<T, Box<dyn Error>>,
+) -> (Result<T, Box<dyn Error>>, Vec<HookRun>) {
+    let timeout = hooks.timeout();
+    let mut runs = Vec::new();
+
+    let result = match run_stage(Stage::Pre, &hooks.pre, env, timeout, &mut runs) {
+        Err(err) => Err(err),
+        Ok(()) => backup().and_then(|value| {
+            run_stage(Stage::Post, &hooks.post, env, timeout, &mut runs)?;
+            Ok(value)
+        }),
+    };
+
+    if let Err(err) = &result {
+        let error = err.to_string();
+        let mut env = env.to_vec();
+        env.push(("STELLARSHOT_ERROR", &error));
+        // the run already failed, the failure hook's own result is only logged
+        let _ = run_stage(Stage::Failure, &hooks.failure, &env, timeout, &mut runs);
+    }
+
+    (result, runs)
+}
+
+fn run_stage(
+    stage: Stage,
+    command: &str,
+    env: &[(&str, &str)],
+    timeout: Duration,
+    runs: &mut Vec<HookRun>,
+) -> Result<(), Box<dyn Error>> {
+    let command = command.trim();
+    if command.is_empty() {
+        return Ok(());
+    }
+
+    let (output, result) = match run(command, env, timeout) {
+        Ok(run) => run,
+        Err(err) => (String::new(), Err(err.to_string())),
+    };
+    runs.push(HookRun {
+        stage,
+        command: command.to_string(),
+        output,
+        result: result.clone(),
+    });
+
+    result.map_err(|err| format!("{stage} hook failed: {err}").into())
+}
+
+/// Runs `command` with `sh -c`, killing it after `timeout`.
+///
+/// Returns the last [`MAX_OUTPUT`] bytes of the combined output and whether the command exited
+/// successfully in time. The output goes to a file read once the command exited, as processes the
+/// command left running in the background would keep a pipe open.
+fn run(
+    command: &str,
+    env: &[(&str, &str)],
+    timeout: Duration,
+) -> Result<(String, Result<(), String>), Box<dyn Error>> {
+    let mut file = output_file()?;
+    let mut child = Command::new("sh")
+        .arg("-c")
+        .arg(command)
+        .envs(env.iter().copied())
+        .stdin(Stdio::null())
+        .stdout(file.try_clone()?)
+        .stderr(file.try_clone()?)
+        // a group of its own, so a timeout also stops the processes the command started
+        .process_group(0)
+        .spawn()?;
+
+    let started = Instant::now();
+    let status = loop {
+        if let Some(status) = child.try_wait()? {
+            break Some(status);
+        }
+        if started.elapsed() >= timeout {
+            let group = libc::pid_t::try_from(child.id())?;
+            // SAFETY: only sends a signal to the process group created for the command
+            unsafe { libc::kill(-group, libc::SIGKILL) };
+            child.wait()?;
+            break None;
+        }
+        thread::sleep(POLL_INTERVAL);
+    };
+
+    let mut output = Vec::new();
+    let truncated = file.metadata()?.len() > MAX_OUTPUT;
+    if truncated {
+        output.extend_from_slice(b"...\n");
+        file.seek(SeekFrom::End(-(MAX_OUTPUT as i64)))?;
+    } else {
+        file.rewind()?;
+    }
+    file.read_to_end(&mut output)?;
+    let output = String::from_utf8_lossy(&output).to_string();
+
+    let result = match status {
+        Some(status) if status.success() => Ok(()),
+        Some(status) => Err(format!("exited with {status}")),
+        None => Err(format!("timed out after {} seconds", timeout.as_secs())),
+    };
+    Ok((output, result))
+}
+
+/// An empty file in the temporary directory, already removed so nothing is left behind.
+fn output_file() -> io::Result<File> {
+    static COUNT: AtomicU64 = AtomicU64::new(0);
+    let name = format!(
+        "stellarshot-hook-{}-{}",
+        std::process::id(),
+        COUNT.fetch_add(1, Ordering::Relaxed)
+    );
+    let path = std::env::temp_dir().join(name);
+    let file = File::options()
+        .read(true)
+        .write(true)
+        .create_new(true)
+        .open(&path)?;
+    fs::remove_file(&path)?;
+    Ok(file)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_run() {
+        let timeout = Duration::from_secs(5);
+
+        let (output, result) = run(
+            "echo $GREETING; echo oops >&2",
+            &[("GREETING", "hi")],
+            timeout,
+        )
+        .unwrap();
+        assert_eq!(output, "hi\noops\n");
+        assert!(result.is_ok());
+
+        let (_, result) = run("exit 2", &[], timeout).unwrap();
+        assert!(result.is_err());
+
+        let started = Instant::now();
+        let (_, result) = run("sleep 10", &[], Duration::from_millis(200)).unwrap();
+        assert!(result.unwrap_err().contains("timed out"));
+        assert!(started.elapsed() < timeout);
+
+        // a process left running in the background is not waited for
+        let started = Instant::now();
+        let (output, result) = run("sleep 10 & echo started", &[], timeout).unwrap();
+        assert_eq!(output, "started\n");
+        assert!(result.is_ok());
+        assert!(started.elapsed() < timeout);
+
+        // only the end of long output is kept
+        let (output, result) = run("seq 100000", &[], timeout).unwrap();
+        assert!(result.is_ok());
+        assert!(output.starts_with("...\n"));
+        assert!(output.ends_with("\n100000\n"));
+        assert_eq!(output.len(), "...\n".len() + MAX_OUTPUT as usize);
+    }
+
+    #[test]
+    fn test_backup_with_hooks() {
+        let hooks = Hooks {
+            pre: "echo pre".into(),
+            post: "echo post".into(),
+            failure: "echo $STELLARSHOT_ERROR".into(),
+            timeout: 5,
+        };
+        let (result, runs) = backup_with_hooks(&hooks, &[], || Ok(1));
+        assert_eq!(result.unwrap(), 1);
+        let stages: Vec<Stage> = runs.iter().map(|run| run.stage).collect();
+        assert_eq!(stages, [Stage::Pre, Stage::Post]);
+
+        let (result, runs) = backup_with_hooks(&hooks, &[], || Err::<(), _>("no space".into()));
+        assert!(result.is_err());
+        assert_eq!(runs[1].stage, Stage::Failure);
+        assert_eq!(runs[1].output, "no space\n");
+    }
+
+    #[test]
+    fn test_pre_hook_failure() {
+        let hooks = Hooks {
+            pre: "exit 1".into(),
+            failure: "echo cleanup".into(),
+            ..Default::default()
+        };
+        let mut ran = false;
+
+        let (result, runs) = backup_with_hooks(&hooks, &[], || {
+            ran = true;
+            Ok(())
+        });
+
+        assert!(result.is_err());
+        assert!(!ran);
+        assert_eq!(runs.len(), 2);
+        assert_eq!(runs[1].output, "cleanup\n");
+    }
+}
@@ -2,6 +2,7 @@ use rustic_backend::BackendOptions;
 use rustic_core::{ConfigOptions, KeyOptions, Repository, RepositoryOptions};
 use std::error::Error;
 
+#[tracing::instrument(skip_all, fields(repo = repository, operation = "init"))]
 pub fn init(repository: &str, password: &str) -> Result<(), Box<dyn Error>> {
     // Initialize Backends
     let backends = BackendOptions::default()
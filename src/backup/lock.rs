@@ -0,0 +1,470 @@
+use aes256ctr_poly1305aes::aead::{Aead, Nonce};
+use aes256ctr_poly1305aes::{Aes256CtrPoly1305Aes, Key};
+use chrono::{DateTime, Duration, Local};
+use once_cell::sync::Lazy;
+use rustic_core::repofile::KeyFile;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::error::Error;
+use std::fmt;
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, RecvTimeoutError};
+use std::sync::Mutex;
+use std::thread::{self, JoinHandle};
+
+/// Locks not refreshed for this long are considered abandoned, as in restic.
+const STALE_AFTER: Duration = Duration::minutes(30);
+
+/// How often held locks are written again with a new time, as in restic.
+const REFRESH_INTERVAL: std::time::Duration = std::time::Duration::from_secs(5 * 60);
+
+/// Keys already derived from a password, by repository and password hash, as deriving one takes
+/// a deliberately expensive scrypt run.
+static KEYS: Lazy<Mutex<HashMap<(String, Vec<u8>), LockKey>>> = Lazy::new(Mutex::default);
+
+/// The lock file format shared with restic.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+struct LockFile {
+    time: DateTime<Local>,
+    exclusive: bool,
+    hostname: String,
+    username: String,
+    pid: u32,
+    uid: u32,
+    gid: u32,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Lock {
+    /// Name of the lock file in the repository's `locks` directory.
+    pub id: String,
+    pub time: DateTime<Local>,
+    pub exclusive: bool,
+    pub hostname: String,
+    pub username: String,
+    pub pid: u32,
+    pub stale: bool,
+    /// The lock file cannot be read, only its id and time are known.
+    pub corrupt: bool,
+}
+
+/// Returned by operations refusing to run while other processes hold locks.
+#[derive(Debug)]
+pub struct LockedError(pub Vec<Lock>);
+
+impl fmt::Display for LockedError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "repository is locked by {} other process(es)",
+            self.0.len()
+        )
+    }
+}
+
+impl Error for LockedError {}
+
+/// Refreshes its lock file while held and removes it when dropped.
+#[derive(Debug)]
+pub struct LockGuard {
+    path: Option<PathBuf>,
+    /// Stops the refresher when dropped, which returns the path of the current lock file.
+    refresher: Option<(mpsc::Sender<()>, JoinHandle<PathBuf>)>,
+}
+
+impl Drop for LockGuard {
+    fn drop(&mut self) {
+        if let Some((stop, refresher)) = self.refresher.take() {
+            drop(stop);
+            match refresher.join() {
+                Ok(path) => self.path = Some(path),
+                Err(_) => log::error!("lock refresher panicked"),
+            }
+        }
+        if let Some(path) = self.path.take() {
+            if let Err(err) = fs::remove_file(&path) {
+                log::error!("failed to remove lock {}: {}", path.display(), err);
+            }
+        }
+    }
+}
+
+/// Lists the locks of a repository.
+///
+/// Only repositories on a local filesystem are supported, others have no locks.
+pub fn locks(repository: &str, password: &str) -> Result<Vec<Lock>, Box<dyn Error>> {
+    if !Path::new(repository).join("locks").is_dir() {
+        return Ok(Vec::new());
+    }
+    read_locks(repository, &LockKey::open(repository, password)?)
+}
+
+/// Deletes the given lock files, if they are still stale.
+///
+/// The locks are read again first, as the process holding a lock may have come back since they
+/// were listed. Returns the number of locks removed.
+pub fn remove(repository: &str, password: &str, ids: &[String]) -> Result<usize, Box<dyn Error>> {
+    let stale: Vec<String> = locks(repository, password)?
+        .into_iter()
+        .filter(|lock| lock.stale && ids.contains(&lock.id))
+        .map(|lock| lock.id)
+        .collect();
+
+    let dir = Path::new(repository).join("locks");
+    for id in &stale {
+        fs::remove_file(dir.join(id))?;
+    }
+    Ok(stale.len())
+}
+
+/// Takes a lock on `repo`, failing with [`LockedError`] if a conflicting lock is held.
+///
+/// Exclusive locks conflict with all other locks, shared locks only with exclusive ones.
+/// Stale locks are ignored. Repositories not on a local filesystem are not locked.
+///
+/// Taking a lock is not atomic: the lock file is written first and the other locks are checked
+/// afterwards, giving it up again on a conflict. Two processes locking at the same time may
+/// both fail, but never both succeed.
+///
+/// The lock file is written again every [`REFRESH_INTERVAL`] until the guard is dropped, so
+/// long operations don't look abandoned to other machines.
+pub fn lock(
+    repository: &str,
+    password: &str,
+    exclusive: bool,
+) -> Result<LockGuard, Box<dyn Error>> {
+    lock_refreshing(repository, password, exclusive, REFRESH_INTERVAL)
+}
+
+fn lock_refreshing(
+    repository: &str,
+    password: &str,
+    exclusive: bool,
+    interval: std::time::Duration,
+) -> Result<LockGuard, Box<dyn Error>> {
+    if !Path::new(repository).join("config").is_file() {
+        return Ok(LockGuard {
+            path: None,
+            refresher: None,
+        });
+    }
+    let key = LockKey::open(repository, password)?;
+    let dir = Path::new(repository).join("locks");
+    fs::create_dir_all(&dir)?;
+
+    let lock = LockFile {
+        time: Local::now(),
+        exclusive,
+        hostname: hostname(),
+        username: std::env::var("USER").unwrap_or_default(),
+        pid: std::process::id(),
+        // SAFETY: getuid and getgid cannot fail
+        uid: unsafe { libc::getuid() },
+        gid: unsafe { libc::getgid() },
+    };
+    let path = write_lock(&key, &dir, &lock)?;
+    let id = path
+        .file_name()
+        .unwrap_or_default()
+        .to_string_lossy()
+        .to_string();
+    // removes the lock file again if it conflicts
+    let mut guard = LockGuard {
+        path: Some(path.clone()),
+        refresher: None,
+    };
+
+    let conflicts: Vec<Lock> = read_locks(repository, &key)?
+        .into_iter()
+        .filter(|lock| lock.id != id && !lock.stale && (exclusive || lock.exclusive))
+        .collect();
+    if !conflicts.is_empty() {
+        return Err(Box::new(LockedError(conflicts)));
+    }
+
+    let (stop, stopped) = mpsc::channel();
+    // the refresher owns the lock file from now on
+    guard.path = None;
+    let refresher = thread::spawn(move || {
+        let (mut lock, mut path) = (lock, path);
+        while let Err(RecvTimeoutError::Timeout) = stopped.recv_timeout(interval) {
+            lock.time = Local::now();
+            match write_lock(&key, &dir, &lock) {
+                Ok(refreshed) => {
+                    if let Err(err) = fs::remove_file(&path) {
+                        log::error!("failed to remove lock {}: {}", path.display(), err);
+                    }
+                    path = refreshed;
+                }
+                Err(err) => log::error!("failed to refresh lock {}: {}", path.display(), err),
+            }
+        }
+        path
+    });
+    guard.refresher = Some((stop, refresher));
+
+    Ok(guard)
+}
+
+/// Writes a new lock file to `dir`, named after the hash of its encrypted contents.
+fn write_lock(key: &LockKey, dir: &Path, lock: &LockFile) -> Result<PathBuf, Box<dyn Error>> {
+    let data = key.encrypt(&serde_json::to_vec(lock)?)?;
+    let path = dir.join(format!("{:x}", Sha256::digest(&data)));
+    fs::OpenOptions::new()
+        .write(true)
+        .create_new(true)
+        .open(&path)?
+        .write_all(&data)?;
+    Ok(path)
+}
+
+fn read_locks(repository: &str, key: &LockKey) -> Result<Vec<Lock>, Box<dyn Error>> {
+    let dir = Path::new(repository).join("locks");
+    if !dir.is_dir() {
+        return Ok(Vec::new());
+    }
+
+    let now = Local::now();
+    let hostname = hostname();
+    let mut locks = Vec::new();
+    for entry in fs::read_dir(dir)? {
+        let entry = entry?;
+        let id = entry.file_name().to_string_lossy().to_string();
+        match read_lock(key, &entry.path()) {
+            Ok(lock) => {
+                let stale = is_stale(&lock, now, &hostname);
+                locks.push(Lock {
+                    id,
+                    time: lock.time,
+                    exclusive: lock.exclusive,
+                    hostname: lock.hostname,
+                    username: lock.username,
+                    pid: lock.pid,
+                    stale,
+                    corrupt: false,
+                });
+            }
+            Err(err) => {
+                log::warn!("failed to read lock {}: {}", id, err);
+                // a lock file being written may not be readable yet, so only old ones are stale
+                let time = entry
+                    .metadata()
+                    .and_then(|metadata| metadata.modified())
+                    .map(DateTime::<Local>::from)
+                    .unwrap_or(now);
+                locks.push(Lock {
+                    id,
+                    time,
+                    exclusive: false,
+                    hostname: String::new(),
+                    username: String::new(),
+                    pid: 0,
+                    stale: now - time > STALE_AFTER,
+                    corrupt: true,
+                });
+            }
+        }
+    }
+    locks.sort_by_key(|lock| lock.time);
+
+    Ok(locks)
+}
+
+fn read_lock(key: &LockKey, path: &Path) -> Result<LockFile, Box<dyn Error>> {
+    let data = key.decrypt(&fs::read(path)?)?;
+    Ok(serde_json::from_slice(&data)?)
+}
+
+/// The master key of a repository, which lock files are encrypted with like all its files.
+///
+/// rustic_core does not expose its own encryption, so lock files are encrypted here in the same
+/// format: a random nonce, the AES-256-CTR ciphertext and a Poly1305-AES tag.
+#[derive(Clone)]
+struct LockKey(Aes256CtrPoly1305Aes);
+
+impl LockKey {
+    /// Finds the key file of the repository `password` opens, deriving its key only once.
+    fn open(repository: &str, password: &str) -> Result<Self, Box<dyn Error>> {
+        let cached = (repository.to_string(), Sha256::digest(password).to_vec());
+        if let Some(key) = KEYS.lock().unwrap().get(&cached) {
+            return Ok(key.clone());
+        }
+        let key = Self::derive(repository, password)?;
+        KEYS.lock().unwrap().insert(cached, key.clone());
+        Ok(key)
+    }
+
+    fn derive(repository: &str, password: &str) -> Result<Self, Box<dyn Error>> {
+        for entry in fs::read_dir(Path::new(repository).join("keys"))? {
+            let file: KeyFile = serde_json::from_slice(&fs::read(entry?.path())?)?;
+            if let Ok(key) = file.key_from_password(&password) {
+                let (encrypt, k, r) = key.to_keys();
+                let key = [encrypt, k, r].concat();
+                return Ok(Self(Aes256CtrPoly1305Aes::new(Key::from_slice(&key))));
+            }
+        }
+        Err("no key of the repository matches the password".into())
+    }
+
+    fn encrypt(&self, data: &[u8]) -> Result<Vec<u8>, Box<dyn Error>> {
+        let nonce: [u8; 16] = rand::random();
+        let encrypted = self
+            .0
+            .encrypt(Nonce::<Aes256CtrPoly1305Aes>::from_slice(&nonce), data)
+            .map_err(|_| "failed to encrypt lock")?;
+        Ok([&nonce[..], &encrypted].concat())
+    }
+
+    /// Decrypts a lock file, which repositories of version 2 may also have compressed.
+    fn decrypt(&self, data: &[u8]) -> Result<Vec<u8>, Box<dyn Error>> {
+        if data.len() < 16 {
+            return Err("lock file too short".into());
+        }
+        let (nonce, encrypted) = data.split_at(16);
+        let data = self
+            .0
+            .decrypt(Nonce::<Aes256CtrPoly1305Aes>::from_slice(nonce), encrypted)
+            .map_err(|_| "failed to decrypt lock")?;
+        match data.first() {
+            // uncompressed JSON starts with `{`, compressed data with its version
+            Some(2) => Ok(zstd::decode_all(&data[1..])?),
+            _ => Ok(data),
+        }
+    }
+}
+
+/// A lock is stale if it is old or held by a process of this machine that no longer runs.
+///
+/// Processes of this machine still running keep their locks, however old.
+fn is_stale(lock: &LockFile, now: DateTime<Local>, hostname: &str) -> bool {
+    if lock.hostname == hostname {
+        return !process_exists(lock.pid);
+    }
+    now - lock.time > STALE_AFTER
+}
+
+fn process_exists(pid: u32) -> bool {
+    let Ok(pid) = libc::pid_t::try_from(pid) else {
+        return false;
+    };
+    // SAFETY: signal 0 only checks whether the process exists
+    let result = unsafe { libc::kill(pid, 0) };
+    result == 0 || std::io::Error::last_os_error().raw_os_error() == Some(libc::EPERM)
+}
+
+fn hostname() -> String {
+    let mut buffer = [0u8; 256];
+    // SAFETY: the buffer is valid for its whole length
+    let result = unsafe { libc::gethostname(buffer.as_mut_ptr().cast(), buffer.len()) };
+    if result != 0 {
+        return String::new();
+    }
+    let length = buffer.iter().position(|b| *b == 0).unwrap_or(buffer.len());
+    String::from_utf8_lossy(&buffer[..length]).to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn lock_file(hostname: &str, pid: u32, age: Duration) -> LockFile {
+        LockFile {
+            time: Local::now() - age,
+            exclusive: false,
+            hostname: hostname.to_string(),
+            username: String::new(),
+            pid,
+            uid: 0,
+            gid: 0,
+        }
+    }
+
+    #[test]
+    fn test_is_stale() {
+        let now = Local::now();
+        let local = hostname();
+        let running = std::process::id();
+
+        assert!(!is_stale(
+            &lock_file("other", 1, Duration::minutes(5)),
+            now,
+            &local
+        ));
+        assert!(is_stale(
+            &lock_file("other", 1, Duration::hours(2)),
+            now,
+            &local
+        ));
+        assert!(!is_stale(
+            &lock_file(&local, running, Duration::hours(2)),
+            now,
+            &local
+        ));
+        assert!(is_stale(
+            &lock_file(&local, u32::MAX, Duration::minutes(1)),
+            now,
+            &local
+        ));
+    }
+
+    #[test]
+    fn test_lock() {
+        let dir = tempfile::tempdir().unwrap();
+        let repository = dir.path().join("repository");
+        let repository = repository.to_str().unwrap();
+        let password = "password";
+        assert!(crate::backup::init(repository, password).is_ok());
+
+        let shared = lock(repository, password, false).unwrap();
+        assert!(lock(repository, password, false).is_ok());
+        let error = lock(repository, password, true).unwrap_err();
+        assert!(error.downcast_ref::<LockedError>().is_some());
+
+        let held = locks(repository, password).unwrap();
+        assert_eq!(held.len(), 1);
+        assert_eq!(held[0].pid, std::process::id());
+        assert!(!held[0].stale);
+        // a running process keeps its lock
+        assert_eq!(
+            remove(repository, password, &[held[0].id.clone()]).unwrap(),
+            0
+        );
+
+        drop(shared);
+        assert!(locks(repository, password).unwrap().is_empty());
+
+        // unreadable lock files are listed instead of failing
+        let corrupt = Path::new(repository).join("locks").join("corrupt");
+        fs::write(&corrupt, b"not a lock").unwrap();
+        let held = locks(repository, password).unwrap();
+        assert_eq!(held.len(), 1);
+        assert!(held[0].corrupt);
+        assert!(!held[0].stale);
+        fs::remove_file(corrupt).unwrap();
+    }
+
+    #[test]
+    fn test_refresh() {
+        let dir = tempfile::tempdir().unwrap();
+        let repository = dir.path().join("repository");
+        let repository = repository.to_str().unwrap();
+        let password = "password";
+        assert!(crate::backup::init(repository, password).is_ok());
+
+        let interval = std::time::Duration::from_millis(100);
+        let guard = lock_refreshing(repository, password, false, interval).unwrap();
+        let taken = locks(repository, password).unwrap();
+        thread::sleep(interval * 3 + interval / 2);
+
+        let refreshed = locks(repository, password).unwrap();
+        assert_eq!(refreshed.len(), 1);
+        assert_ne!(refreshed[0].id, taken[0].id);
+        assert!(refreshed[0].time > taken[0].time);
+
+        drop(guard);
+        assert!(locks(repository, password).unwrap().is_empty());
+    }
+}
@@ -0,0 +1,71 @@
+use rustic_backend::BackendOptions;
+use rustic_core::{CheckOptions, PruneOptions, Repository, RepositoryOptions};
+use std::error::Error;
+
+use crate::backup::lock::lock;
+
+/// Verifies the structure of the repository, all snapshots, trees and packs.
+#[tracing::instrument(skip_all, fields(repo = repository, operation = "check"))]
+pub fn check(repository: &str, password: &str) -> Result<(), Box<dyn Error>> {
+    let backends = BackendOptions::default()
+        .repository(repository)
+        .to_backends()?;
+
+    let _lock = lock(repository, password, false)?;
+
+    let repo_opts = RepositoryOptions::default().password(password);
+
+    let repo = Repository::new(&repo_opts, backends)?.open()?;
+
+    repo.check(CheckOptions::default())?;
+
+    Ok(())
+}
+
+/// Removes the data no snapshot references anymore, repacking partly used packs.
+#[tracing::instrument(skip_all, fields(repo = repository, operation = "prune"))]
+pub fn prune(repository: &str, password: &str) -> Result<(), Box<dyn Error>> {
+    let backends = BackendOptions::default()
+        .repository(repository)
+        .to_backends()?;
+
+    // no other process may add data that would only be referenced after pruning
+    let _lock = lock(repository, password, true)?;
+
+    let repo_opts = RepositoryOptions::default().password(password);
+
+    let repo = Repository::new(&repo_opts, backends)?.open()?;
+
+    let prune_opts = PruneOptions::default();
+    let plan = repo.prune_plan(&prune_opts)?;
+    plan.do_prune(&repo, &prune_opts)?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::backup::{init, snapshot, snapshot::delete, snapshot::fetch};
+
+    #[test]
+    fn test_check_and_prune() {
+        let dir = tempfile::tempdir().unwrap();
+        let repository = dir.path().join("repository");
+        let repository = repository.to_str().unwrap();
+        let password = "password";
+        let (first, second) = (dir.path().join("first"), dir.path().join("second"));
+        std::fs::write(&first, "first").unwrap();
+        std::fs::write(&second, "second").unwrap();
+
+        assert!(init(repository, password).is_ok());
+        assert!(snapshot(repository, password, vec![first.to_str().unwrap()]).is_ok());
+        assert!(snapshot(repository, password, vec![second.to_str().unwrap()]).is_ok());
+        let first = fetch(repository, password).unwrap()[0].id;
+        assert!(delete(repository, password, vec![first]).is_ok());
+
+        assert!(prune(repository, password).is_ok());
+        assert!(check(repository, password).is_ok());
+        assert_eq!(fetch(repository, password).unwrap().len(), 1);
+    }
+}
@@ -0,0 +1,487 @@
+use chrono::{DateTime, Local};
+use fuser::{
+    BackgroundSession, FileAttr, FileType, Filesystem, MountOption, ReplyAttr, ReplyData,
+    ReplyDirectory, ReplyEmpty, ReplyEntry, ReplyOpen, Request, FUSE_ROOT_ID,
+};
+use libc::{EIO, ENOENT, ENOTDIR};
+use rustic_backend::BackendOptions;
+use rustic_core::{
+    repofile::{Node, NodeType, SnapshotFile},
+    vfs::OpenFile,
+    IndexedFull, LsOptions, ProgressBars, Repository, RepositoryOptions,
+};
+use std::collections::{BTreeMap, HashMap};
+use std::error::Error;
+use std::ffi::{OsStr, OsString};
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::{Duration, SystemTime};
+
+const TTL: Duration = Duration::from_secs(60);
+
+/// A mounted repository, unmounted when dropped.
+pub struct Mount {
+    pub mountpoint: PathBuf,
+    session: Mutex<Option<BackgroundSession>>,
+}
+
+impl Mount {
+    pub fn unmount(&self) {
+        if let Some(session) = self.session.lock().unwrap().take() {
+            session.join();
+        }
+    }
+}
+
+impl Drop for Mount {
+    fn drop(&mut self) {
+        self.unmount();
+    }
+}
+
+impl fmt::Debug for Mount {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Mount")
+            .field("mountpoint", &self.mountpoint)
+            .finish()
+    }
+}
+
+/// Mounts all snapshots of a repository read-only at `mountpoint`.
+///
+/// The snapshots are listed in `by-time`, `by-host` and `by-tag` directories.
+pub fn mount(repository: &str, password: &str, mountpoint: &Path) -> Result<Mount, Box<dyn Error>> {
+    let backends = BackendOptions::default()
+        .repository(repository)
+        .to_backends()?;
+
+    let repo_opts = RepositoryOptions::default().password(password);
+
+    let repo = Repository::new(&repo_opts, backends)?
+        .open()?
+        .to_indexed()?;
+
+    let mut snapshots = repo.get_all_snapshots()?;
+    snapshots.sort_unstable_by_key(|snapshot| snapshot.time);
+
+    let filesystem = SnapshotFs::new(repo, &snapshots)?;
+    let options = [
+        MountOption::RO,
+        MountOption::FSName("stellarshot".to_string()),
+        MountOption::DefaultPermissions,
+    ];
+    let session = fuser::spawn_mount2(filesystem, mountpoint, &options)?;
+
+    Ok(Mount {
+        mountpoint: mountpoint.to_path_buf(),
+        session: Mutex::new(Some(session)),
+    })
+}
+
+enum Entry {
+    /// A directory grouping snapshots.
+    Virtual {
+        parent: u64,
+        children: BTreeMap<OsString, u64>,
+    },
+    /// A node inside a snapshot, its children are read when first accessed.
+    Node {
+        parent: u64,
+        node: Box<Node>,
+        children: Option<BTreeMap<OsString, u64>>,
+    },
+}
+
+struct SnapshotFs<P, S> {
+    repo: Repository<P, S>,
+    /// Indexed by inode - 1.
+    entries: Vec<Entry>,
+    open_files: HashMap<u64, OpenFile>,
+    next_handle: u64,
+    mounted: SystemTime,
+    uid: u32,
+    gid: u32,
+}
+
+impl<P: ProgressBars, S: IndexedFull> SnapshotFs<P, S> {
+    fn new(repo: Repository<P, S>, snapshots: &[SnapshotFile]) -> Result<Self, Box<dyn Error>> {
+        let mut fs = Self {
+            repo,
+            entries: Vec::new(),
+            open_files: HashMap::new(),
+            next_handle: 0,
+            mounted: SystemTime::now(),
+            // SAFETY: both calls cannot fail
+            uid: unsafe { libc::getuid() },
+            gid: unsafe { libc::getgid() },
+        };
+
+        let root = fs.add_virtual(FUSE_ROOT_ID);
+        let by_time = fs.add_virtual_child(root, "by-time".into());
+        let by_host = fs.add_virtual_child(root, "by-host".into());
+        let by_tag = fs.add_virtual_child(root, "by-tag".into());
+
+        for snapshot in snapshots {
+            let node = fs.repo.node_from_snapshot_and_path(snapshot, "")?;
+            let name = snapshot_name(&snapshot.time);
+
+            fs.add_snapshot(by_time, &name, snapshot, &node);
+
+            let host = fs.virtual_child(by_host, snapshot.hostname.clone().into());
+            fs.add_snapshot(host, &name, snapshot, &node);
+
+            for tag in snapshot.tags.iter() {
+                let tag = fs.virtual_child(by_tag, tag.clone().into());
+                fs.add_snapshot(tag, &name, snapshot, &node);
+            }
+        }
+
+        Ok(fs)
+    }
+
+    fn add_virtual(&mut self, parent: u64) -> u64 {
+        self.entries.push(Entry::Virtual {
+            parent,
+            children: BTreeMap::new(),
+        });
+        self.entries.len() as u64
+    }
+
+    fn add_virtual_child(&mut self, parent: u64, name: OsString) -> u64 {
+        let ino = self.add_virtual(parent);
+        self.insert_child(parent, name, ino);
+        ino
+    }
+
+    /// Returns the virtual directory `name` in `parent`, creating it if needed.
+    fn virtual_child(&mut self, parent: u64, name: OsString) -> u64 {
+        let existing = self
+            .children(parent)
+            .and_then(|children| children.get(&name))
+            .copied();
+        match existing {
+            Some(ino) => ino,
+            None => self.add_virtual_child(parent, name),
+        }
+    }
+
+    fn add_snapshot(&mut self, parent: u64, name: &str, snapshot: &SnapshotFile, node: &Node) {
+        // snapshots taken within the same second get their id appended
+        let mut name = OsString::from(name);
+        if self
+            .children(parent)
+            .is_some_and(|children| children.contains_key(&name))
+        {
+            name.push(format!(" {}", snapshot.id));
+        }
+
+        self.entries.push(Entry::Node {
+            parent,
+            node: Box::new(node.clone()),
+            children: None,
+        });
+        let ino = self.entries.len() as u64;
+        self.insert_child(parent, name, ino);
+    }
+
+    fn insert_child(&mut self, parent: u64, name: OsString, ino: u64) {
+        match self.entry_mut(parent) {
+            Some(Entry::Virtual { children, .. })
+            | Some(Entry::Node {
+                children: Some(children),
+                ..
+            }) => {
+                children.insert(name, ino);
+            }
+            _ => {}
+        }
+    }
+
+    fn entry(&self, ino: u64) -> Option<&Entry> {
+        self.entries.get(ino.checked_sub(1)? as usize)
+    }
+
+    fn entry_mut(&mut self, ino: u64) -> Option<&mut Entry> {
+        self.entries.get_mut(ino.checked_sub(1)? as usize)
+    }
+
+    fn children(&self, ino: u64) -> Option<&BTreeMap<OsString, u64>> {
+        match self.entry(ino)? {
+            Entry::Virtual { children, .. } => Some(children),
+            Entry::Node { children, .. } => children.as_ref(),
+        }
+    }
+
+    /// Reads the children of a directory node from the repository on first access.
+    fn load_children(&mut self, ino: u64) -> Result<(), i32> {
+        let node = match self.entry(ino) {
+            Some(Entry::Virtual { .. }) => return Ok(()),
+            Some(Entry::Node {
+                children: Some(_), ..
+            }) => return Ok(()),
+            Some(Entry::Node { node, .. }) if node.is_dir() => Node::clone(node),
+            Some(Entry::Node { .. }) => return Err(ENOTDIR),
+            None => return Err(ENOENT),
+        };
+
+        let ls_opts = LsOptions {
+            recursive: false,
+            ..Default::default()
+        };
+        let mut nodes = Vec::new();
+        for entry in self.repo.ls(&node, &ls_opts).map_err(|_| EIO)? {
+            let (_, child) = entry.map_err(|_| EIO)?;
+            nodes.push(child);
+        }
+
+        if let Some(Entry::Node { children, .. }) = self.entry_mut(ino) {
+            *children = Some(BTreeMap::new());
+        }
+        for child in nodes {
+            let name = child.name();
+            self.entries.push(Entry::Node {
+                parent: ino,
+                node: Box::new(child),
+                children: None,
+            });
+            let child_ino = self.entries.len() as u64;
+            self.insert_child(ino, name, child_ino);
+        }
+
+        Ok(())
+    }
+
+    fn attr(&self, ino: u64) -> Option<FileAttr> {
+        let attr = FileAttr {
+            ino,
+            size: 0,
+            blocks: 0,
+            atime: self.mounted,
+            mtime: self.mounted,
+            ctime: self.mounted,
+            crtime: self.mounted,
+            kind: FileType::Directory,
+            perm: 0o555,
+            nlink: 1,
+            uid: self.uid,
+            gid: self.gid,
+            rdev: 0,
+            blksize: 4096,
+            flags: 0,
+        };
+
+        match self.entry(ino)? {
+            Entry::Virtual { .. } => Some(attr),
+            Entry::Node { node, .. } => {
+                let meta = &node.meta;
+                let time = |time: &Option<DateTime<Local>>| {
+                    time.map(SystemTime::from).unwrap_or(self.mounted)
+                };
+                Some(FileAttr {
+                    size: meta.size,
+                    blocks: meta.size.div_ceil(512),
+                    atime: time(&meta.atime),
+                    mtime: time(&meta.mtime),
+                    ctime: time(&meta.ctime),
+                    kind: file_type(&node.node_type),
+                    perm: meta.mode.map_or(0o555, |mode| (mode & 0o7777) as u16),
+                    uid: meta.uid.unwrap_or(self.uid),
+                    gid: meta.gid.unwrap_or(self.gid),
+                    ..attr
+                })
+            }
+        }
+    }
+}
+
+impl<P, S> Filesystem for SnapshotFs<P, S>
+where
+    P: ProgressBars,
+    S: IndexedFull,
+{
+    fn lookup(&mut self, _req: &Request<'_>, parent: u64, name: &OsStr, reply: ReplyEntry) {
+        if let Err(error) = self.load_children(parent) {
+            return reply.error(error);
+        }
+
+        let attr = self
+            .children(parent)
+            .and_then(|children| children.get(name))
+            .and_then(|ino| self.attr(*ino));
+        match attr {
+            Some(attr) => reply.entry(&TTL, &attr, 0),
+            None => reply.error(ENOENT),
+        }
+    }
+
+    fn getattr(&mut self, _req: &Request<'_>, ino: u64, reply: ReplyAttr) {
+        match self.attr(ino) {
+            Some(attr) => reply.attr(&TTL, &attr),
+            None => reply.error(ENOENT),
+        }
+    }
+
+    fn readlink(&mut self, _req: &Request<'_>, ino: u64, reply: ReplyData) {
+        let Some(Entry::Node { node, .. }) = self.entry(ino) else {
+            return reply.error(ENOENT);
+        };
+        match &node.node_type {
+            NodeType::Symlink { linktarget, .. } => reply.data(linktarget.as_bytes()),
+            _ => reply.error(ENOENT),
+        }
+    }
+
+    fn open(&mut self, _req: &Request<'_>, ino: u64, _flags: i32, reply: ReplyOpen) {
+        let Some(Entry::Node { node, .. }) = self.entry(ino) else {
+            return reply.error(ENOENT);
+        };
+
+        match self.repo.open_file(node) {
+            Ok(file) => {
+                self.next_handle += 1;
+                self.open_files.insert(self.next_handle, file);
+                reply.opened(self.next_handle, 0);
+            }
+            Err(_) => reply.error(EIO),
+        }
+    }
+
+    fn read(
+        &mut self,
+        _req: &Request<'_>,
+        ino: u64,
+        fh: u64,
+        offset: i64,
+        size: u32,
+        _flags: i32,
+        _lock_owner: Option<u64>,
+        reply: ReplyData,
+    ) {
+        let (Some(Entry::Node { node, .. }), Some(file)) =
+            (self.entry(ino), self.open_files.get(&fh))
+        else {
+            return reply.error(ENOENT);
+        };
+
+        let offset = offset.max(0) as u64;
+        let length = node.meta.size.saturating_sub(offset).min(u64::from(size));
+        if length == 0 {
+            return reply.data(&[]);
+        }
+
+        match self
+            .repo
+            .read_file_at(file, offset as usize, length as usize)
+        {
+            Ok(data) => reply.data(&data),
+            Err(_) => reply.error(EIO),
+        }
+    }
+
+    fn release(
+        &mut self,
+        _req: &Request<'_>,
+        _ino: u64,
+        fh: u64,
+        _flags: i32,
+        _lock_owner: Option<u64>,
+        _flush: bool,
+        reply: ReplyEmpty,
+    ) {
+        self.open_files.remove(&fh);
+        reply.ok();
+    }
+
+    fn readdir(
+        &mut self,
+        _req: &Request<'_>,
+        ino: u64,
+        _fh: u64,
+        offset: i64,
+        mut reply: ReplyDirectory,
+    ) {
+        if let Err(error) = self.load_children(ino) {
+            return reply.error(error);
+        }
+
+        let parent = match self.entry(ino) {
+            Some(Entry::Virtual { parent, .. }) | Some(Entry::Node { parent, .. }) => *parent,
+            None => return reply.error(ENOENT),
+        };
+
+        let mut entries = vec![
+            (ino, FileType::Directory, OsString::from(".")),
+            (parent, FileType::Directory, OsString::from("..")),
+        ];
+        for (name, child) in self.children(ino).into_iter().flatten() {
+            let kind = self
+                .attr(*child)
+                .map_or(FileType::RegularFile, |attr| attr.kind);
+            entries.push((*child, kind, name.clone()));
+        }
+
+        for (i, (ino, kind, name)) in entries.into_iter().enumerate().skip(offset as usize) {
+            if reply.add(ino, (i + 1) as i64, kind, name) {
+                break;
+            }
+        }
+        reply.ok();
+    }
+}
+
+fn snapshot_name(time: &DateTime<Local>) -> String {
+    time.format("%Y-%m-%d %H:%M:%S").to_string()
+}
+
+fn file_type(node_type: &NodeType) -> FileType {
+    match node_type {
+        NodeType::Dir => FileType::Directory,
+        NodeType::Symlink { .. } => FileType::Symlink,
+        NodeType::Dev { .. } => FileType::BlockDevice,
+        NodeType::Chardev { .. } => FileType::CharDevice,
+        NodeType::Fifo => FileType::NamedPipe,
+        NodeType::Socket => FileType::Socket,
+        NodeType::File => FileType::RegularFile,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::backup::{init, snapshot, snapshot::fetch};
+
+    #[test]
+    #[ignore = "needs /dev/fuse, run with `cargo test -- --ignored`"]
+    fn test_mount() {
+        let dir = tempfile::tempdir().unwrap();
+        let repository = dir.path().join("repository");
+        let repository = repository.to_str().unwrap();
+        let password = "password";
+        let source = dir.path().join("source");
+        let mountpoint = dir.path().join("mount");
+        std::fs::create_dir(&source).unwrap();
+        std::fs::create_dir(&mountpoint).unwrap();
+        std::fs::write(source.join("file"), "content").unwrap();
+
+        assert!(init(repository, password).is_ok());
+        assert!(snapshot(repository, password, vec![source.to_str().unwrap()]).is_ok());
+        let snapshots = fetch(repository, password).unwrap();
+        let name = snapshot_name(&snapshots[0].time);
+
+        let mount = mount(repository, password, &mountpoint).unwrap();
+        let snapshot_dir = mountpoint.join("by-time").join(&name);
+        let file = snapshot_dir
+            .join(source.strip_prefix("/").unwrap())
+            .join("file");
+        assert_eq!(std::fs::read_to_string(&file).unwrap(), "content");
+        let hosts: Vec<_> = std::fs::read_dir(mountpoint.join("by-host"))
+            .unwrap()
+            .collect();
+        assert_eq!(hosts.len(), 1);
+        assert!(std::fs::write(&file, "changed").is_err());
+
+        mount.unmount();
+        assert!(!snapshot_dir.exists());
+    }
+}
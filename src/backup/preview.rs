@@ -0,0 +1,135 @@
+use rustic_backend::BackendOptions;
+use rustic_core::{Repository, RepositoryOptions};
+use std::error::Error;
+use std::fs::File;
+use std::path::Path;
+
+/// Larger images are not previewed.
+pub const IMAGE_LIMIT: u64 = 4 * 1024 * 1024;
+
+/// Only the beginning of longer text files is shown, the text widget gets slow with more.
+pub const TEXT_LIMIT: u64 = 32 * 1024;
+
+const IMAGE_EXTENSIONS: &[&str] = &["png", "jpg", "jpeg", "gif", "webp", "bmp", "ico"];
+
+#[derive(Clone, Debug)]
+pub enum Preview {
+    Text { content: String, truncated: bool },
+    Image(Vec<u8>),
+    TooLarge(u64),
+    Unsupported,
+}
+
+/// Reads the beginning of the file at `snap_path` (`snapshot:path`) without restoring it.
+pub fn preview(
+    repository: &str,
+    password: &str,
+    snap_path: &str,
+) -> Result<Preview, Box<dyn Error>> {
+    let backends = BackendOptions::default()
+        .repository(repository)
+        .to_backends()?;
+
+    let repo_opts = RepositoryOptions::default().password(password);
+
+    let repo = Repository::new(&repo_opts, backends)?
+        .open()?
+        .to_indexed()?;
+
+    let node = repo.node_from_snapshot_path(snap_path, |_| true)?;
+    if !node.is_file() {
+        return Ok(Preview::Unsupported);
+    }
+
+    let size = node.meta.size;
+    let image = is_image(Path::new(&node.name()));
+    if image && size > IMAGE_LIMIT {
+        return Ok(Preview::TooLarge(size));
+    }
+
+    let limit = if image { IMAGE_LIMIT } else { TEXT_LIMIT };
+    let file = repo.open_file(&node)?;
+    let data = repo.read_file_at(&file, 0, size.min(limit) as usize)?;
+
+    if image {
+        return Ok(Preview::Image(data.to_vec()));
+    }
+
+    Ok(match decode_text(&data) {
+        Some(content) => Preview::Text {
+            content,
+            truncated: size > TEXT_LIMIT,
+        },
+        None => Preview::Unsupported,
+    })
+}
+
+/// Writes the single file at `snap_path` (`snapshot:path`) to `destination`.
+pub fn save(
+    repository: &str,
+    password: &str,
+    snap_path: &str,
+    destination: &Path,
+) -> Result<(), Box<dyn Error>> {
+    let backends = BackendOptions::default()
+        .repository(repository)
+        .to_backends()?;
+
+    let repo_opts = RepositoryOptions::default().password(password);
+
+    let repo = Repository::new(&repo_opts, backends)?
+        .open()?
+        .to_indexed()?;
+
+    let node = repo.node_from_snapshot_path(snap_path, |_| true)?;
+    let mut file = File::create(destination)?;
+    repo.dump(&node, &mut file)?;
+
+    Ok(())
+}
+
+fn is_image(path: &Path) -> bool {
+    path.extension()
+        .and_then(|extension| extension.to_str())
+        .is_some_and(|extension| {
+            IMAGE_EXTENSIONS
+                .iter()
+                .any(|image| image.eq_ignore_ascii_case(extension))
+        })
+}
+
+/// Decodes UTF-8 text, tolerating a character cut off by the preview limit.
+fn decode_text(data: &[u8]) -> Option<String> {
+    if data.contains(&0) {
+        return None;
+    }
+
+    match std::str::from_utf8(data) {
+        Ok(text) => Some(text.to_string()),
+        Err(error) if error.error_len().is_none() => {
+            Some(String::from_utf8_lossy(&data[..error.valid_up_to()]).to_string())
+        }
+        Err(_) => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_image() {
+        assert!(is_image(Path::new("photo.JPG")));
+        assert!(!is_image(Path::new("notes.txt")));
+        assert!(!is_image(Path::new("png")));
+    }
+
+    #[test]
+    fn test_decode_text() {
+        let text = "grüße".as_bytes();
+
+        assert_eq!(decode_text(text).as_deref(), Some("grüße"));
+        assert_eq!(decode_text(&text[..3]).as_deref(), Some("gr"));
+        assert_eq!(decode_text(b"\x00\x01binary"), None);
+    }
+}
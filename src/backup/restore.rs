@@ -1,8 +1,10 @@
 use rustic_backend::BackendOptions;
 use rustic_core::{LocalDestination, LsOptions, Repository, RepositoryOptions, RestoreOptions};
 use std::error::Error;
+use std::path::Path;
 
-#[allow(dead_code)]
+/// Restores `snap_path` (`snapshot[:path]`) into the directory `restore_destination`.
+#[tracing::instrument(skip_all, fields(repo = repository, operation = "restore"))]
 pub fn restore(
     repository: &str,
     password: &str,
@@ -27,9 +29,15 @@ pub fn restore(
     let streamer_opts = LsOptions::default();
     let ls = repo.ls(&node, &streamer_opts)?;
 
-    let destination = restore_destination; // restore to this destination dir
+    // a single file is restored into the destination dir under its own name
+    let destination = if node.is_dir() {
+        Path::new(restore_destination).to_path_buf()
+    } else {
+        Path::new(restore_destination).join(node.name())
+    };
+    let destination = destination.to_string_lossy(); // restore to this destination
     let create = true; // create destination dir, if it doesn't exist
-    let dest = LocalDestination::new(destination, create, !node.is_dir())?;
+    let dest = LocalDestination::new(&destination, create, !node.is_dir())?;
 
     let opts = RestoreOptions::default();
     let dry_run = false;
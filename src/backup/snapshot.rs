@@ -1,48 +1,264 @@
 use rustic_backend::BackendOptions;
 use rustic_core::{
-    repofile::SnapshotFile, BackupOptions, PathList, Repository, RepositoryOptions, SnapshotOptions,
+    repofile::SnapshotFile, BackupOptions, LocalSourceFilterOptions, ParentOptions, PathList,
+    Repository, RepositoryOptions, SnapshotGroupCriterion, SnapshotOptions, StringList,
 };
+use serde::{Deserialize, Serialize};
+use std::env;
 use std::error::Error;
+use std::fs::{self, File};
+use std::path::{Component, Path, PathBuf};
+use std::process::{self, Stdio};
+
+use crate::backup::lock::lock;
+
+/// What a backup profile stores in its snapshots.
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub enum Source {
+    Paths(Vec<PathBuf>),
+    /// The standard output of a shell command, such as a database dump, stored as one file.
+    Command {
+        command: String,
+        filename: String,
+    },
+}
+
+impl Default for Source {
+    fn default() -> Self {
+        Source::Paths(Vec::new())
+    }
+}
+
+/// The snapshot a backup compares files with, unchanged files are taken from it without reading
+/// them again.
+#[derive(Clone, Debug, Default, Eq, PartialEq, Serialize, Deserialize)]
+pub enum Parent {
+    /// The latest snapshot of the same host and paths.
+    #[default]
+    Latest,
+    /// A snapshot given by its id or a unique prefix of it.
+    Snapshot(String),
+    /// No parent, every file is read again even if its size, mtime, ctime and inode are unchanged.
+    Rescan,
+}
+
+/// Which changes to a file's metadata make a backup read it again although its size and mtime
+/// are the same as in the parent.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ChangeDetection {
+    /// Files are not read again for a changed ctime, which e.g. changing permissions updates.
+    pub ignore_ctime: bool,
+    /// Files are not read again for a changed inode, as on filesystems without stable inodes.
+    pub ignore_inode: bool,
+}
+
+impl Parent {
+    fn options(&self, detection: ChangeDetection) -> Result<ParentOptions, Box<dyn Error>> {
+        let options = ParentOptions::default()
+            .ignore_ctime(detection.ignore_ctime)
+            .ignore_inode(detection.ignore_inode);
+        Ok(match self {
+            Parent::Latest => {
+                // rustic's default also requires the same label
+                let group_by: SnapshotGroupCriterion = "host,paths".parse()?;
+                options.group_by(group_by)
+            }
+            Parent::Snapshot(id) => options.parent(id.trim().to_string()),
+            Parent::Rescan => options.force(true),
+        })
+    }
+}
 
 pub fn snapshot(repository: &str, password: &str, paths: Vec<&str>) -> Result<(), Box<dyn Error>> {
+    snapshot_excluding(
+        repository,
+        password,
+        paths,
+        &[],
+        &Parent::default(),
+        ChangeDetection::default(),
+    )
+    .map(|_| ())
+}
+
+/// Creates a snapshot of `source`, the exclude rules only apply to paths.
+pub fn backup(
+    repository: &str,
+    password: &str,
+    source: &Source,
+    excludes: &[String],
+    parent: &Parent,
+    detection: ChangeDetection,
+) -> Result<SnapshotFile, Box<dyn Error>> {
+    match source {
+        Source::Paths(paths) => {
+            let paths = paths.iter().filter_map(|path| path.to_str()).collect();
+            snapshot_excluding(repository, password, paths, excludes, parent, detection)
+        }
+        Source::Command { command, filename } => {
+            snapshot_command(repository, password, command, filename, parent, detection)
+        }
+    }
+}
+
+/// Creates a snapshot of `paths`, leaving out everything matching one of the `excludes` globs.
+#[tracing::instrument(skip_all, fields(repo = repository, operation = "backup"))]
+pub fn snapshot_excluding(
+    repository: &str,
+    password: &str,
+    paths: Vec<&str>,
+    excludes: &[String],
+    parent: &Parent,
+    detection: ChangeDetection,
+) -> Result<SnapshotFile, Box<dyn Error>> {
     // Initialize Backends
     let backends = BackendOptions::default()
         .repository(repository)
         .to_backends()?;
 
-    println!("successfully initialized backends:\n{backends:#?}");
+    tracing::debug!(?backends, "initialized backends");
 
     // Open repository
+    // held before the index is read, so no prune removes packs it lists
+    let _lock = lock(repository, password, false)?;
+
     let repo_opts = RepositoryOptions::default().password(password);
 
     let repo = Repository::new(&repo_opts, backends)?
         .open()?
         .to_indexed_ids()?;
 
-    println!("successfully opened repository:\n{repo:#?}");
+    tracing::debug!("opened repository");
 
-    let backup_opts = BackupOptions::default();
+    // rustic's globs include matching files unless negated
+    let globs: Vec<String> = excludes.iter().map(|glob| format!("!{glob}")).collect();
+    let filter_opts = LocalSourceFilterOptions::default().glob(globs);
+    let backup_opts = BackupOptions::default()
+        .parent_opts(parent.options(detection)?)
+        .ignore_filter_opts(filter_opts);
     let source = PathList::from_strings(paths).sanitize()?;
 
-    println!("successfully sanitized paths:\n{source:#?}");
+    tracing::debug!(?source, "sanitized paths");
 
     let snap = SnapshotOptions::default().to_snapshot()?;
 
-    println!("successfully created snapshot options:\n{snap:#?}");
-
     // Create snapshot
     let snap = repo.backup(&backup_opts, &source, snap)?;
 
-    println!("successfully created snapshot:\n{snap:#?}");
-    Ok(())
+    log_summary(&snap);
+    Ok(snap)
 }
 
+/// Runs `command` with `sh -c` and stores its standard output as `filename` in a new snapshot.
+///
+/// The output is written to a temporary file first, which is backed up under `filename`, so the
+/// whole dump needs free space in the cache directory next to rustic's repository caches for as
+/// long as the backup runs. No snapshot is created if the command fails, and `filename` must be a
+/// relative path without `..` components (a leading `/` is ignored).
+#[tracing::instrument(skip_all, fields(repo = repository, operation = "backup"))]
+pub fn snapshot_command(
+    repository: &str,
+    password: &str,
+    command: &str,
+    filename: &str,
+    parent: &Parent,
+    detection: ChangeDetection,
+) -> Result<SnapshotFile, Box<dyn Error>> {
+    let relative = Path::new(filename.trim_start_matches('/'));
+    if relative.as_os_str().is_empty()
+        || !relative
+            .components()
+            .all(|component| matches!(component, Component::Normal(_)))
+    {
+        return Err(format!("invalid file name `{filename}`").into());
+    }
+
+    let backends = BackendOptions::default()
+        .repository(repository)
+        .to_backends()?;
+
+    let _lock = lock(repository, password, false)?;
+
+    let repo_opts = RepositoryOptions::default().password(password);
+
+    let repo = Repository::new(&repo_opts, backends)?
+        .open()?
+        .to_indexed_ids()?;
+
+    // keep large dumps off a tmpfs `/tmp`, on the same disk as the repository caches
+    let cache = cache_dir();
+    fs::create_dir_all(&cache)?;
+    let dir = tempfile::Builder::new()
+        .prefix("stellarshot-")
+        .tempdir_in(&cache)?;
+    let path = dir.path().join(relative);
+    if let Some(directory) = path.parent() {
+        fs::create_dir_all(directory)?;
+    }
+    let output = process::Command::new("sh")
+        .arg("-c")
+        .arg(command)
+        .stdin(Stdio::null())
+        .stdout(File::create(&path)?)
+        .stderr(Stdio::piped())
+        .output()?;
+    if !output.status.success() {
+        return Err(format!(
+            "`{command}` failed with {}: {}",
+            output.status,
+            String::from_utf8_lossy(&output.stderr).trim()
+        )
+        .into());
+    }
+
+    let backup_opts = BackupOptions::default()
+        .as_path(PathBuf::from(filename))
+        .parent_opts(parent.options(detection)?);
+    let source = PathList::from_string(path.to_str().ok_or("invalid file name")?)?;
+    let snap = SnapshotOptions::default().to_snapshot()?;
+
+    let snap = repo.backup(&backup_opts, &source, snap)?;
+
+    log_summary(&snap);
+    Ok(snap)
+}
+
+fn log_summary(snap: &SnapshotFile) {
+    let Some(summary) = &snap.summary else {
+        return;
+    };
+    tracing::info!(
+        snapshot = %snap.id,
+        parent = ?snap.parent,
+        files_reused = summary.files_unmodified,
+        files = summary.total_files_processed,
+        bytes = summary.total_bytes_processed,
+        bytes_added = summary.data_added,
+        duration = summary.total_duration,
+        "created snapshot"
+    );
+}
+
+/// Where rustic keeps its repository caches, `$XDG_CACHE_HOME/rustic`.
+fn cache_dir() -> PathBuf {
+    let cache_home = env::var_os("XDG_CACHE_HOME")
+        .filter(|dir| !dir.is_empty())
+        .map(PathBuf::from)
+        .unwrap_or_else(|| {
+            let home = env::var_os("HOME").map(PathBuf::from).unwrap_or_default();
+            home.join(".cache")
+        });
+    cache_home.join("rustic")
+}
+
+#[tracing::instrument(skip_all, fields(repo = repository, operation = "snapshots"))]
 pub fn fetch(repository: &str, password: &str) -> Result<Vec<SnapshotFile>, Box<dyn Error>> {
     let backends = BackendOptions::default()
         .repository(repository)
         .to_backends()?;
 
-    println!("successfully initialized backends:\n{backends:#?}");
+    tracing::debug!(?backends, "initialized backends");
 
     let repo_opts = RepositoryOptions::default().password(password);
 
@@ -55,6 +271,7 @@ pub fn fetch(repository: &str, password: &str) -> Result<Vec<SnapshotFile>, Box<
     Ok(snapshots)
 }
 
+#[tracing::instrument(skip_all, fields(repo = repository, operation = "delete"))]
 pub fn delete(
     repository: &str,
     password: &str,
@@ -64,7 +281,9 @@ pub fn delete(
         .repository(repository)
         .to_backends()?;
 
-    println!("successfully initialized backends:\n{backends:#?}");
+    tracing::debug!(?backends, "initialized backends");
+
+    let _lock = lock(repository, password, true)?;
 
     let repo_opts = RepositoryOptions::default().password(password);
 
@@ -77,6 +296,52 @@ pub fn delete(
     Ok(())
 }
 
+/// Adds `tags` to the given snapshots.
+///
+/// Snapshots are immutable, so each changed snapshot is saved under a new id and the old one is
+/// removed.
+#[tracing::instrument(skip_all, fields(repo = repository, operation = "tag"))]
+pub fn tag(
+    repository: &str,
+    password: &str,
+    snapshots: Vec<rustic_core::Id>,
+    tags: Vec<String>,
+) -> Result<(), Box<dyn Error>> {
+    let backends = BackendOptions::default()
+        .repository(repository)
+        .to_backends()?;
+
+    let _lock = lock(repository, password, true)?;
+
+    let repo_opts = RepositoryOptions::default().password(password);
+
+    let repo = Repository::new(&repo_opts, backends)?
+        .open()?
+        .to_indexed_ids()?;
+
+    let mut tag_list = StringList::default();
+    for tag in tags {
+        tag_list.add(tag);
+    }
+
+    let changed: Vec<SnapshotFile> = repo
+        .get_all_snapshots()?
+        .into_iter()
+        .filter(|snapshot| snapshots.contains(&snapshot.id))
+        .filter_map(|mut snapshot| {
+            snapshot
+                .add_tags(vec![tag_list.clone()])
+                .then_some(snapshot)
+        })
+        .collect();
+    let old_ids: Vec<rustic_core::Id> = changed.iter().map(|snapshot| snapshot.id).collect();
+
+    repo.save_snapshots(changed)?;
+    repo.delete_snapshots(&old_ids)?;
+
+    Ok(())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -89,4 +354,186 @@ mod tests {
 
         assert!(snapshot(repository, password, paths).is_ok());
     }
+
+    #[test]
+    fn test_snapshot_excluding() {
+        let dir = tempfile::tempdir().unwrap();
+        let repository = dir.path().join("repository");
+        let repository = repository.to_str().unwrap();
+        let password = "password";
+        let source = dir.path().join("source");
+        std::fs::create_dir_all(source.join("cache")).unwrap();
+        std::fs::write(source.join("cache/data"), "cached").unwrap();
+        std::fs::write(source.join("file"), "content").unwrap();
+
+        assert!(crate::backup::init(repository, password).is_ok());
+        let excludes = vec![source.join("cache").display().to_string()];
+        let paths = vec![source.to_str().unwrap()];
+        let parent = Parent::default();
+        let detection = ChangeDetection::default();
+        assert!(
+            snapshot_excluding(repository, password, paths, &excludes, &parent, detection).is_ok()
+        );
+
+        let snapshots = fetch(repository, password).unwrap();
+        let summary = snapshots[0].summary.as_ref().unwrap();
+        assert_eq!(summary.total_files_processed, 1);
+    }
+
+    #[test]
+    fn test_snapshot_command() {
+        let dir = tempfile::tempdir().unwrap();
+        let repository = dir.path().join("repository");
+        let repository = repository.to_str().unwrap();
+        let password = "password";
+
+        assert!(crate::backup::init(repository, password).is_ok());
+        let snap = snapshot_command(
+            repository,
+            password,
+            "echo dump",
+            "dump.sql",
+            &Parent::default(),
+            ChangeDetection::default(),
+        )
+        .unwrap();
+        assert!(snap.paths.iter().any(|path| path == "dump.sql"));
+        let summary = snap.summary.as_ref().unwrap();
+        assert_eq!(summary.total_bytes_processed, 5);
+
+        let error = snapshot_command(
+            repository,
+            password,
+            "echo partial; exit 3",
+            "dump.sql",
+            &Parent::default(),
+            ChangeDetection::default(),
+        );
+        assert!(error.unwrap_err().to_string().contains("failed"));
+        assert_eq!(fetch(repository, password).unwrap().len(), 1);
+
+        for filename in ["../../escaped", "dumps/../../escaped", "/", ""] {
+            let error = snapshot_command(
+                repository,
+                password,
+                "echo dump",
+                filename,
+                &Parent::default(),
+                ChangeDetection::default(),
+            );
+            assert!(error.unwrap_err().to_string().contains("invalid file name"));
+        }
+        assert_eq!(fetch(repository, password).unwrap().len(), 1);
+
+        // nothing of an earlier command is left over for the next one
+        let snap = snapshot_command(
+            repository,
+            password,
+            "echo dump",
+            "dump.sql",
+            &Parent::default(),
+            ChangeDetection::default(),
+        )
+        .unwrap();
+        assert_eq!(snap.summary.as_ref().unwrap().total_bytes_processed, 5);
+    }
+
+    #[test]
+    fn test_parent() {
+        let dir = tempfile::tempdir().unwrap();
+        let repository = dir.path().join("repository");
+        let repository = repository.to_str().unwrap();
+        let password = "password";
+        let file = dir.path().join("file");
+        std::fs::write(&file, "content").unwrap();
+        let source = Source::Paths(vec![file]);
+        let run = |parent: &Parent| {
+            backup(
+                repository,
+                password,
+                &source,
+                &[],
+                parent,
+                ChangeDetection::default(),
+            )
+            .unwrap()
+        };
+
+        assert!(crate::backup::init(repository, password).is_ok());
+        let first = run(&Parent::Latest);
+        assert_eq!(first.parent, None);
+
+        let second = run(&Parent::Latest);
+        assert_eq!(second.parent, Some(first.id));
+        assert_eq!(second.summary.as_ref().unwrap().files_unmodified, 1);
+
+        let explicit = Parent::Snapshot(first.id.to_hex().to_string());
+        let third = run(&explicit);
+        assert_eq!(third.parent, Some(first.id));
+
+        let rescan = run(&Parent::Rescan);
+        assert_eq!(rescan.parent, None);
+        assert_eq!(rescan.summary.as_ref().unwrap().files_unmodified, 0);
+    }
+
+    #[test]
+    fn test_change_detection() {
+        let dir = tempfile::tempdir().unwrap();
+        let repository = dir.path().join("repository");
+        let repository = repository.to_str().unwrap();
+        let password = "password";
+        let file = dir.path().join("file");
+        std::fs::write(&file, "content").unwrap();
+        let source = Source::Paths(vec![file.clone()]);
+        let unmodified = |detection| {
+            // setting the same permissions only changes the ctime
+            std::thread::sleep(std::time::Duration::from_millis(50));
+            let permissions = std::fs::metadata(&file).unwrap().permissions();
+            std::fs::set_permissions(&file, permissions).unwrap();
+            let snap = backup(
+                repository,
+                password,
+                &source,
+                &[],
+                &Parent::Latest,
+                detection,
+            )
+            .unwrap();
+            snap.summary.unwrap().files_unmodified
+        };
+
+        assert!(crate::backup::init(repository, password).is_ok());
+        assert_eq!(unmodified(ChangeDetection::default()), 0);
+        assert_eq!(unmodified(ChangeDetection::default()), 0);
+        let ignore_ctime = ChangeDetection {
+            ignore_ctime: true,
+            ..Default::default()
+        };
+        assert_eq!(unmodified(ignore_ctime), 1);
+    }
+
+    #[test]
+    fn test_tag() {
+        let dir = tempfile::tempdir().unwrap();
+        let repository = dir.path().join("repository");
+        let repository = repository.to_str().unwrap();
+        let password = "password";
+        let source = dir.path().join("source");
+        std::fs::create_dir(&source).unwrap();
+        std::fs::write(source.join("file"), "content").unwrap();
+
+        assert!(crate::backup::init(repository, password).is_ok());
+        assert!(snapshot(repository, password, vec![source.to_str().unwrap()]).is_ok());
+        let ids = fetch(repository, password)
+            .unwrap()
+            .iter()
+            .map(|snapshot| snapshot.id)
+            .collect();
+
+        assert!(tag(repository, password, ids, vec!["daily".to_string()]).is_ok());
+
+        let snapshots = fetch(repository, password).unwrap();
+        assert_eq!(snapshots.len(), 1);
+        assert!(snapshots[0].tags.iter().any(|tag| tag == "daily"));
+    }
 }
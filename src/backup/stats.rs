@@ -0,0 +1,137 @@
+use chrono::NaiveDate;
+use rustic_backend::BackendOptions;
+use rustic_core::{
+    repofile::{BlobType, SnapshotFile},
+    Repository, RepositoryOptions,
+};
+use std::collections::BTreeMap;
+use std::error::Error;
+
+#[derive(Clone, Debug, Default)]
+pub struct Statistics {
+    pub snapshots: usize,
+    /// Size of the data read by all backups together.
+    pub total_size: u64,
+    /// Size of the file data after deduplication, before compression.
+    pub unique_size: u64,
+    /// Size of the same file data as stored in the packs.
+    pub compressed_size: u64,
+    pub packs: u64,
+    pub blobs: u64,
+    /// Size of the blobs in packs already marked for deletion.
+    pub pending_delete_size: u64,
+    /// Repository size added by the snapshots up to the end of each day with a snapshot.
+    pub growth: Vec<(NaiveDate, u64)>,
+}
+
+impl Statistics {
+    pub fn compression_ratio(&self) -> f64 {
+        ratio(self.unique_size, self.compressed_size)
+    }
+
+    pub fn dedup_ratio(&self) -> f64 {
+        ratio(self.total_size, self.unique_size)
+    }
+}
+
+pub fn statistics(repository: &str, password: &str) -> Result<Statistics, Box<dyn Error>> {
+    let backends = BackendOptions::default()
+        .repository(repository)
+        .to_backends()?;
+
+    let repo_opts = RepositoryOptions::default().password(password);
+
+    let repo = Repository::new(&repo_opts, backends)?.open()?;
+
+    let index = repo.infos_index()?;
+    let snapshots = repo.get_all_snapshots()?;
+
+    let mut statistics = Statistics {
+        snapshots: snapshots.len(),
+        total_size: snapshots
+            .iter()
+            .filter_map(|snapshot| snapshot.summary.as_ref())
+            .map(|summary| summary.total_bytes_processed)
+            .sum(),
+        growth: growth(&snapshots),
+        ..Default::default()
+    };
+
+    for blob in &index.blobs {
+        statistics.blobs += blob.count;
+        // trees are left out of both sizes, so the compression ratio compares the same blobs
+        if blob.blob_type == BlobType::Data {
+            statistics.unique_size += blob.data_size;
+            statistics.compressed_size += blob.size;
+        }
+    }
+    statistics.packs = index.packs.iter().map(|pack| pack.count).sum();
+    statistics.pending_delete_size = index.blobs_delete.iter().map(|blob| blob.size).sum();
+
+    Ok(statistics)
+}
+
+fn growth(snapshots: &[SnapshotFile]) -> Vec<(NaiveDate, u64)> {
+    let mut added: BTreeMap<NaiveDate, u64> = BTreeMap::new();
+    for snapshot in snapshots {
+        let size = snapshot
+            .summary
+            .as_ref()
+            .map_or(0, |summary| summary.data_added_packed);
+        *added.entry(snapshot.time.date_naive()).or_default() += size;
+    }
+
+    added
+        .into_iter()
+        .scan(0, |total, (date, size)| {
+            *total += size;
+            Some((date, *total))
+        })
+        .collect()
+}
+
+fn ratio(numerator: u64, denominator: u64) -> f64 {
+    match denominator {
+        0 => 0.0,
+        denominator => numerator as f64 / denominator as f64,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::backup::{init, snapshot};
+
+    #[test]
+    fn test_statistics() {
+        let dir = tempfile::tempdir().unwrap();
+        let repository = dir.path().join("repository");
+        let repository = repository.to_str().unwrap();
+        let password = "password";
+        let source = dir.path().join("source");
+        std::fs::create_dir(&source).unwrap();
+        std::fs::write(source.join("file"), "content".repeat(1000)).unwrap();
+        let paths = || vec![source.to_str().unwrap()];
+
+        assert!(init(repository, password).is_ok());
+        assert!(snapshot(repository, password, paths()).is_ok());
+        assert!(snapshot(repository, password, paths()).is_ok());
+
+        let statistics = statistics(repository, password).unwrap();
+
+        assert_eq!(statistics.snapshots, 2);
+        assert!(statistics.packs > 0);
+        assert!(statistics.blobs > 0);
+        assert!(statistics.unique_size <= statistics.total_size);
+        assert!(statistics.compressed_size > 0);
+        // the second snapshot adds nothing new
+        assert!(statistics.dedup_ratio() > 1.5);
+        assert_eq!(statistics.growth.len(), 1);
+    }
+
+    #[test]
+    fn test_ratio() {
+        assert_eq!(ratio(10, 4), 2.5);
+        assert_eq!(ratio(10, 0), 0.0);
+    }
+}
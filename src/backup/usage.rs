@@ -0,0 +1,125 @@
+use rustic_backend::BackendOptions;
+use rustic_core::{Id, LsOptions, Repository, RepositoryOptions};
+use std::collections::HashMap;
+use std::error::Error;
+use std::path::{Path, PathBuf};
+
+/// The size of a directory in a snapshot, including everything below it.
+#[derive(Clone, Debug, Default)]
+pub struct DirUsage {
+    /// Path of the directory within the snapshot, empty for the root.
+    pub path: PathBuf,
+    pub size: u64,
+    pub files: u64,
+    pub dirs: Vec<DirUsage>,
+}
+
+impl DirUsage {
+    pub fn name(&self) -> String {
+        self.path
+            .file_name()
+            .map(|name| name.to_string_lossy().to_string())
+            .unwrap_or_else(|| String::from("/"))
+    }
+
+    /// The directory's absolute path, as used by exclude rules.
+    pub fn absolute_path(&self) -> PathBuf {
+        Path::new("/").join(&self.path)
+    }
+}
+
+/// Sums up the sizes of the files in a snapshot per directory.
+pub fn usage(repository: &str, password: &str, snapshot: Id) -> Result<DirUsage, Box<dyn Error>> {
+    let backends = BackendOptions::default()
+        .repository(repository)
+        .to_backends()?;
+
+    let repo_opts = RepositoryOptions::default().password(password);
+
+    let repo = Repository::new(&repo_opts, backends)?
+        .open()?
+        .to_indexed_ids()?;
+
+    let snapshot = repo.get_snapshot_from_str(snapshot.to_hex().as_str(), |_| true)?;
+    let root = repo.node_from_snapshot_and_path(&snapshot, "")?;
+
+    let mut entries = Vec::new();
+    for entry in repo.ls(&root, &LsOptions::default())? {
+        let (path, node) = entry?;
+        entries.push((path, node.is_dir(), node.meta.size));
+    }
+
+    Ok(usage_tree(entries))
+}
+
+/// Builds the directory tree from `(path, is_dir, size)` entries, largest directories first.
+fn usage_tree(entries: impl IntoIterator<Item = (PathBuf, bool, u64)>) -> DirUsage {
+    let mut totals: HashMap<PathBuf, (u64, u64)> = HashMap::from([(PathBuf::new(), (0, 0))]);
+    let mut children: HashMap<PathBuf, Vec<PathBuf>> = HashMap::new();
+
+    for (path, is_dir, size) in entries {
+        if is_dir {
+            totals.entry(path.clone()).or_default();
+            let parent = path.parent().unwrap_or(Path::new("")).to_path_buf();
+            children.entry(parent).or_default().push(path);
+        } else {
+            for ancestor in path.ancestors().skip(1) {
+                let (total, files) = totals.entry(ancestor.to_path_buf()).or_default();
+                *total += size;
+                *files += 1;
+            }
+        }
+    }
+
+    build(PathBuf::new(), &totals, &mut children)
+}
+
+fn build(
+    path: PathBuf,
+    totals: &HashMap<PathBuf, (u64, u64)>,
+    children: &mut HashMap<PathBuf, Vec<PathBuf>>,
+) -> DirUsage {
+    let (size, files) = totals.get(&path).copied().unwrap_or_default();
+    let mut dirs: Vec<DirUsage> = children
+        .remove(&path)
+        .unwrap_or_default()
+        .into_iter()
+        .map(|child| build(child, totals, children))
+        .collect();
+    dirs.sort_by_key(|dir| std::cmp::Reverse(dir.size));
+
+    DirUsage {
+        path,
+        size,
+        files,
+        dirs,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_usage_tree() {
+        let entries = vec![
+            (PathBuf::from("home"), true, 0),
+            (PathBuf::from("home/docs"), true, 0),
+            (PathBuf::from("home/docs/report.odt"), false, 300),
+            (PathBuf::from("home/music"), true, 0),
+            (PathBuf::from("home/music/song.ogg"), false, 5000),
+            (PathBuf::from("home/notes.txt"), false, 20),
+        ];
+
+        let root = usage_tree(entries);
+
+        assert_eq!(root.size, 5320);
+        assert_eq!(root.files, 3);
+        let home = &root.dirs[0];
+        assert_eq!(home.name(), "home");
+        assert_eq!(home.absolute_path(), Path::new("/home"));
+        assert_eq!(home.dirs[0].name(), "music");
+        assert_eq!(home.dirs[0].size, 5000);
+        assert_eq!(home.dirs[1].size, 300);
+    }
+}
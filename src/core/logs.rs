@@ -0,0 +1,87 @@
+// SPDX-License-Identifier: GPL-3.0-only
+
+//! The optional log file, rotated daily in the state directory.
+
+use std::error::Error;
+use std::fs::{self, File};
+use std::io::{self, Read, Seek, SeekFrom};
+use std::path::{Path, PathBuf};
+
+use crate::backup::history::state_dir;
+
+/// Prefix of the log files, the date of the day is appended to it.
+pub const LOG_FILE_PREFIX: &str = "stellarshot.log";
+
+/// How many daily log files are kept before the oldest is removed.
+pub const MAX_LOG_FILES: usize = 7;
+
+/// Only the end of the log is shown, long logs would make the viewer unusable.
+const TAIL_BYTES: u64 = 64 * 1024;
+
+pub fn log_dir() -> PathBuf {
+    state_dir().join("logs")
+}
+
+/// The end of the newest log file, `None` if nothing was logged to a file yet.
+pub fn tail() -> Result<Option<String>, Box<dyn Error>> {
+    let dir = log_dir();
+    if !dir.exists() {
+        return Ok(None);
+    }
+
+    // the date suffixes sort chronologically
+    let newest = fs::read_dir(&dir)?
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path())
+        .filter(|path| {
+            path.file_name()
+                .and_then(|name| name.to_str())
+                .is_some_and(|name| name.starts_with(LOG_FILE_PREFIX))
+        })
+        .max();
+    let Some(newest) = newest else {
+        return Ok(None);
+    };
+
+    let log = read_tail(&newest, TAIL_BYTES)?;
+    Ok(Some(String::from_utf8_lossy(&log).into_owned()))
+}
+
+/// Reads the last whole lines of the file at `path` within its last `bytes` bytes, without
+/// reading the rest of it.
+fn read_tail(path: &Path, bytes: u64) -> io::Result<Vec<u8>> {
+    let mut file = File::open(path)?;
+    let cut = file.metadata()?.len() > bytes;
+    if cut {
+        // one byte more tells whether the tail starts at a whole line
+        file.seek(SeekFrom::End(-(bytes as i64) - 1))?;
+    }
+
+    let mut tail = Vec::new();
+    file.read_to_end(&mut tail)?;
+    if cut {
+        let start = tail
+            .iter()
+            .position(|byte| *byte == b'\n')
+            .map_or(tail.len(), |newline| newline + 1);
+        tail.drain(..start);
+    }
+    Ok(tail)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_read_tail() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("log");
+        fs::write(&path, "first\nsecond\nthird\n").unwrap();
+
+        assert_eq!(read_tail(&path, 1024).unwrap(), b"first\nsecond\nthird\n");
+        assert_eq!(read_tail(&path, 10).unwrap(), b"third\n");
+        assert_eq!(read_tail(&path, 13).unwrap(), b"second\nthird\n");
+        assert!(read_tail(&path, 3).unwrap().is_empty());
+    }
+}
@@ -1,3 +1,6 @@
 // SPDX-License-Identifier: GPL-3.0-only
 
 pub mod localization;
+pub mod logs;
+pub mod notifications;
+pub mod secrets;
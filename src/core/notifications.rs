@@ -0,0 +1,269 @@
+// SPDX-License-Identifier: GPL-3.0-only
+
+//! Desktop notifications through the freedesktop notification service, so profile backups and
+//! mirror syncs finishing while the window is in the background are noticed.
+//!
+//! Profiles cannot be scheduled yet, so backups only notify when started by hand; mirror syncs
+//! also notify when their policy runs them.
+
+use cosmic::iced::futures::{stream, StreamExt};
+use std::collections::HashMap;
+use std::pin::pin;
+use std::time::Duration;
+use zbus::zvariant::Value;
+use zbus::Connection;
+
+const APP_NAME: &str = "Stellarshot";
+const APP_ICON: &str = "com.github.ahoneybun.Stellarshot";
+
+/// How long an answer to a notification is waited for, servers keeping notifications around
+/// may never close them.
+pub const ANSWER_TIMEOUT: Duration = Duration::from_secs(60 * 60);
+
+#[zbus::proxy(
+    interface = "org.freedesktop.Notifications",
+    default_service = "org.freedesktop.Notifications",
+    default_path = "/org/freedesktop/Notifications"
+)]
+trait Notifications {
+    #[allow(clippy::too_many_arguments)]
+    fn notify(
+        &self,
+        app_name: &str,
+        replaces_id: u32,
+        app_icon: &str,
+        summary: &str,
+        body: &str,
+        actions: &[&str],
+        hints: HashMap<&str, Value<'_>>,
+        expire_timeout: i32,
+    ) -> zbus::Result<u32>;
+
+    #[zbus(signal)]
+    fn action_invoked(&self, id: u32, action_key: String) -> zbus::Result<()>;
+
+    #[zbus(signal)]
+    fn notification_closed(&self, id: u32, reason: u32) -> zbus::Result<()>;
+}
+
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct Notification {
+    pub summary: String,
+    pub body: String,
+    /// Shown as critical, for failures.
+    pub urgent: bool,
+    /// The keys and labels of the buttons offered.
+    pub actions: Vec<(String, String)>,
+}
+
+/// Shows `notification` on the session bus, see [`show_on`].
+pub async fn show(notification: &Notification) -> zbus::Result<Option<String>> {
+    let connection = Connection::session().await?;
+    show_on(&connection, notification, ANSWER_TIMEOUT).await
+}
+
+/// Shows `notification` and waits until it is closed, at most for `timeout`.
+///
+/// Returns the key of the action the user chose, `None` if the notification was dismissed, has no
+/// actions or was not answered in time. The timeout needs a Tokio runtime, which the application
+/// runs its commands on.
+pub async fn show_on(
+    connection: &Connection,
+    notification: &Notification,
+    timeout: Duration,
+) -> zbus::Result<Option<String>> {
+    let proxy = NotificationsProxy::new(connection).await?;
+    // subscribed before sending, so an answer coming right away is not missed
+    let invoked = proxy.receive_action_invoked().await?;
+    let closed = proxy.receive_notification_closed().await?;
+
+    let actions: Vec<&str> = notification
+        .actions
+        .iter()
+        .flat_map(|(key, label)| [key.as_str(), label.as_str()])
+        .collect();
+    let urgency: u8 = match notification.urgent {
+        true => 2,
+        false => 1,
+    };
+    let hints = HashMap::from([("urgency", Value::from(urgency))]);
+    let id = proxy
+        .notify(
+            APP_NAME,
+            0,
+            APP_ICON,
+            &notification.summary,
+            &notification.body,
+            &actions,
+            hints,
+            -1,
+        )
+        .await?;
+
+    if actions.is_empty() {
+        return Ok(None);
+    }
+
+    let invoked = invoked.filter_map(move |signal| async move {
+        let args = signal.args().ok()?;
+        (*args.id() == id).then(|| Some(args.action_key().clone()))
+    });
+    let closed = closed.filter_map(move |signal| async move {
+        let args = signal.args().ok()?;
+        (*args.id() == id).then_some(None)
+    });
+    let mut answers = pin!(stream::select(invoked, closed));
+    tokio::select! {
+        answer = answers.next() => Ok(answer.flatten()),
+        () = tokio::time::sleep(timeout) => Ok(None),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::{BufRead, BufReader};
+    use std::process::{Child, Command, Stdio};
+    use std::sync::{Arc, Mutex};
+    use zbus::zvariant::OwnedValue;
+    use zbus::{connection, interface, SignalContext};
+
+    /// A notification server recording the summary, body and actions it got, which answers each
+    /// notification with its first action, unless that is `ignore`.
+    struct MockServer {
+        received: Arc<Mutex<Vec<(String, String, Vec<String>)>>>,
+    }
+
+    #[interface(name = "org.freedesktop.Notifications")]
+    impl MockServer {
+        #[allow(clippy::too_many_arguments)]
+        async fn notify(
+            &self,
+            #[zbus(signal_context)] ctxt: SignalContext<'_>,
+            _app_name: String,
+            _replaces_id: u32,
+            _app_icon: String,
+            summary: String,
+            body: String,
+            actions: Vec<String>,
+            _hints: HashMap<String, OwnedValue>,
+            _expire_timeout: i32,
+        ) -> zbus::fdo::Result<u32> {
+            let id = {
+                let mut received = self.received.lock().unwrap();
+                received.push((summary, body, actions.clone()));
+                received.len() as u32
+            };
+            if let Some(key) = actions.first().filter(|key| *key != "ignore") {
+                Self::action_invoked(&ctxt, id, key).await?;
+            }
+            Ok(id)
+        }
+
+        #[zbus(signal)]
+        async fn action_invoked(
+            ctxt: &SignalContext<'_>,
+            id: u32,
+            action_key: &str,
+        ) -> zbus::Result<()>;
+    }
+
+    /// A message bus of its own, so the test does not depend on a desktop session. The daemon is
+    /// killed when this is dropped, even if an assertion failed.
+    struct PrivateBus {
+        daemon: Child,
+        address: String,
+    }
+
+    impl PrivateBus {
+        fn start() -> Self {
+            let mut daemon = Command::new("dbus-daemon")
+                .args(["--session", "--nofork", "--print-address"])
+                .stdout(Stdio::piped())
+                .spawn()
+                .expect("failed to start dbus-daemon");
+            let stdout = daemon.stdout.take().unwrap();
+            // built before reading, so the daemon is not left running if that fails
+            let mut bus = PrivateBus {
+                daemon,
+                address: String::new(),
+            };
+            BufReader::new(stdout).read_line(&mut bus.address).unwrap();
+            bus.address.truncate(bus.address.trim_end().len());
+            bus
+        }
+    }
+
+    impl Drop for PrivateBus {
+        fn drop(&mut self) {
+            let _ = self.daemon.kill();
+            let _ = self.daemon.wait();
+        }
+    }
+
+    #[test]
+    #[ignore = "needs dbus-daemon, run with `cargo test -- --ignored`"]
+    fn test_show() {
+        let bus = PrivateBus::start();
+        let address = &bus.address;
+
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .unwrap();
+        runtime.block_on(async {
+            let received = Arc::default();
+            let _server = connection::Builder::address(address.as_str())
+                .unwrap()
+                .name("org.freedesktop.Notifications")
+                .unwrap()
+                .serve_at(
+                    "/org/freedesktop/Notifications",
+                    MockServer {
+                        received: Arc::clone(&received),
+                    },
+                )
+                .unwrap()
+                .build()
+                .await
+                .unwrap();
+            let client = connection::Builder::address(address.as_str())
+                .unwrap()
+                .build()
+                .await
+                .unwrap();
+
+            let notification = Notification {
+                summary: "Backup home finished".into(),
+                body: "12 MB added in 3 s".into(),
+                urgent: false,
+                actions: vec![("open-snapshot".into(), "Show snapshot".into())],
+            };
+            let timeout = Duration::from_secs(5);
+            let action = show_on(&client, &notification, timeout).await.unwrap();
+            assert_eq!(action.as_deref(), Some("open-snapshot"));
+
+            let silent = Notification {
+                actions: Vec::new(),
+                ..notification.clone()
+            };
+            assert_eq!(show_on(&client, &silent, timeout).await.unwrap(), None);
+
+            // an unanswered notification is given up on
+            let unanswered = Notification {
+                actions: vec![("ignore".into(), "Ignore".into())],
+                ..notification
+            };
+            let started = std::time::Instant::now();
+            let timeout = Duration::from_millis(200);
+            assert_eq!(show_on(&client, &unanswered, timeout).await.unwrap(), None);
+            assert!(started.elapsed() >= timeout);
+
+            let received = received.lock().unwrap();
+            assert_eq!(received.len(), 3);
+            assert_eq!(received[0].0, "Backup home finished");
+            assert_eq!(received[0].1, "12 MB added in 3 s");
+            assert_eq!(received[0].2, ["open-snapshot", "Show snapshot"]);
+        });
+    }
+}
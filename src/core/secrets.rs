@@ -0,0 +1,43 @@
+// SPDX-License-Identifier: GPL-3.0-only
+
+//! Repository passwords kept in the desktop's secret service, so background tasks can open
+//! repositories without asking.
+
+use keyring::Entry;
+use std::error::Error;
+use std::path::Path;
+
+const SERVICE: &str = "com.github.ahoneybun.Stellarshot";
+
+/// The stored password of the repository at `repository`, if any.
+pub fn password(repository: &Path) -> Option<String> {
+    let entry = entry(repository).ok()?;
+    match entry.get_password() {
+        Ok(password) => Some(password),
+        Err(keyring::Error::NoEntry) => None,
+        Err(err) => {
+            log::error!(
+                "failed to read password of {}: {}",
+                repository.display(),
+                err
+            );
+            None
+        }
+    }
+}
+
+pub fn set_password(repository: &Path, password: &str) -> Result<(), Box<dyn Error>> {
+    entry(repository)?.set_password(password)?;
+    Ok(())
+}
+
+pub fn delete_password(repository: &Path) -> Result<(), Box<dyn Error>> {
+    match entry(repository)?.delete_password() {
+        Ok(()) | Err(keyring::Error::NoEntry) => Ok(()),
+        Err(err) => Err(err.into()),
+    }
+}
+
+fn entry(repository: &Path) -> Result<Entry, keyring::Error> {
+    Entry::new(SERVICE, &repository.display().to_string())
+}